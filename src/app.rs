@@ -2,7 +2,7 @@ use std::collections::HashSet;
 use std::ops::Not;
 use std::path::PathBuf;
 use std::sync::mpsc::Sender;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::Error;
 use rand::Rng;
@@ -11,14 +11,22 @@ use tui::layout::Rect;
 use crate::config::user_config::UserConfig;
 use crate::event::IoEvent;
 use crate::handlers::search::SearchResults;
+use crate::http::account::Account;
+use crate::http::error::ApiError;
+use crate::http::{account_manager, read_country_code};
 use crate::model::album::AlbumDetail;
 use crate::model::artist::{Artist, ArtistDetail};
-use crate::model::context::{CurrentlyPlaybackContext, DialogContext};
+use crate::model::context::{CurrentlyPlaybackContext, DialogContext, TrackTableContext};
 use crate::model::dialog::Dialog;
+use crate::model::discover::Discover;
 use crate::model::enums::{RepeatState, ToggleState};
-use crate::model::login::LoginInfo;
+use crate::model::fm::PersonalFm;
+use crate::model::intelligence::Intelligence;
+use crate::model::login::{LoginInfo, LoginMode, QrLoginState, DEFAULT_COUNTRY_CODE};
 use crate::model::playlist::Playlist;
+use crate::model::radio::{filter_new_tracks, Radio};
 use crate::model::table::TrackTable;
+use crate::model::toplist::Toplist;
 use crate::model::track::{Lyric, Track};
 use crate::model::user::UserProfile;
 use crate::util;
@@ -29,7 +37,19 @@ pub const DEFAULT_ROUTE: Route = Route {
     hovered_block: ActiveBlock::Library,
 };
 
-pub const LIBRARY_OPTIONS: [&str; 4] = ["我喜欢", "最近播放", "每日推荐", "关注歌手"];
+pub const LIBRARY_OPTIONS: [&str; 7] = [
+    "我喜欢",
+    "最近播放",
+    "每日推荐",
+    "私人FM",
+    "关注歌手",
+    "排行榜",
+    "发现歌单",
+];
+// 心动模式待播放的歌曲少于该数量时获取更多
+const INTELLIGENCE_REFILL_THRESHOLD: usize = 3;
+// 查询扫码登录状态的间隔
+const QR_LOGIN_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 
 #[derive(Clone)]
 pub struct Library {
@@ -64,9 +84,25 @@ pub enum ActiveBlock {
     Artists,
     ArtistDetail,
     AlbumTracks,
+    // 账号列表
+    Accounts,
+    // 私人FM
+    PersonalFm,
+    // 排行榜
+    Toplists,
+    // 发现歌单的分类列表
+    DiscoverCategories,
+    // 发现歌单的歌单列表
+    DiscoverPlaylists,
     PhoneBlock,
     PasswordBlock,
+    EmailBlock,
     LoginButton,
+    CaptchaBlock,
+    SendCaptchaButton,
+    CountryBlock,
+    QrCodeBlock,
+    CookieBlock,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -82,6 +118,10 @@ pub enum RouteId {
     Artists,
     ArtistDetail,
     AlbumTracks,
+    Accounts,
+    PersonalFm,
+    Toplists,
+    Discover,
     #[allow(unused)]
     PhoneBlock,
     #[allow(unused)]
@@ -148,6 +188,13 @@ pub struct App {
     pub track_table: TrackTable,
     // 接口错误
     pub api_error: String,
+    // 登录已过期，需要回到登录页
+    pub is_session_expired: bool,
+    // 退出登录或添加账号，需要回到登录页
+    pub is_logout: bool,
+    // 已保存的账号
+    pub accounts: Vec<Account>,
+    pub accounts_selected_index: usize,
     pub dialog: Option<Dialog>,
     pub user: Option<UserProfile>,
     pub is_fetching_current_playback: bool,
@@ -156,6 +203,11 @@ pub struct App {
     pub title: String,
     // 当前播放歌曲所属的歌曲列表
     pub current_play_tracks: TrackTable,
+    pub personal_fm: PersonalFm,
+    pub intelligence: Intelligence,
+    pub radio: Radio,
+    // 本次运行中在私人FM里不喜欢的歌曲，生成电台时跳过，不会保存到本地
+    pub disliked_track_ids: HashSet<usize>,
     // 喜欢的歌曲hashset
     pub liked_track_ids_set: HashSet<usize>,
     // 歌词
@@ -165,6 +217,9 @@ pub struct App {
     pub artists: Vec<Artist>,
     pub artist_sub_ids_set: HashSet<usize>,
     pub artists_selected_index: usize,
+    pub toplists: Vec<Toplist>,
+    pub toplists_selected_index: usize,
+    pub discover: Discover,
     pub artist_detail: Option<ArtistDetail>,
     pub album_detail: Option<AlbumDetail>,
     pub login_info: LoginInfo,
@@ -184,6 +239,11 @@ impl App {
             io_tx: Some(io_tx),
             user_config,
             size: Rect::default(),
+            login_info: LoginInfo {
+                country_code: read_country_code()
+                    .unwrap_or_else(|| DEFAULT_COUNTRY_CODE.to_string()),
+                ..LoginInfo::default()
+            },
             ..App::default()
         }
     }
@@ -262,10 +322,153 @@ impl App {
     }
 
     pub fn handle_error(&mut self, e: Error) {
+        if e.downcast_ref::<ApiError>() == Some(&ApiError::Auth) {
+            self.is_session_expired = true;
+        }
         self.push_navigation_stack(RouteId::Error, ActiveBlock::Error);
         self.api_error = e.to_string();
     }
 
+    // 清除当前用户的数据，用于退出登录和切换账号
+    pub fn clear_user_data(&mut self) {
+        self.user = None;
+        self.playlists = None;
+        self.playlist_offset = 0;
+        self.selected_playlist_index = None;
+        self.active_playlist_index = None;
+        self.sub_playlists = None;
+        self.sub_playlist_offset = 0;
+        self.selected_sub_playlist_index = None;
+        self.active_sub_playlist_index = None;
+        self.my_like_playlist_id = 0;
+        self.track_table = TrackTable::default();
+        self.personal_fm = PersonalFm::default();
+        // 心动模式的歌曲随账号清除，不能保留心动模式
+        self.exit_intelligence();
+        self.radio = Radio::default();
+        self.disliked_track_ids.clear();
+        self.liked_track_ids_set.clear();
+        self.artists.clear();
+        self.artist_sub_ids_set.clear();
+        self.artists_selected_index = 0;
+        self.artist_detail = None;
+        self.album_detail = None;
+        self.navigation_stack = vec![DEFAULT_ROUTE];
+    }
+
+    // 打开账号列表
+    pub fn open_accounts(&mut self) {
+        self.accounts = account_manager()
+            .map(|manager| manager.accounts())
+            .unwrap_or_default();
+        self.accounts_selected_index = 0;
+        self.push_navigation_stack(RouteId::Accounts, ActiveBlock::Accounts);
+    }
+
+    pub fn is_playing_fm(&self) -> bool {
+        self.current_play_tracks.context == Some(TrackTableContext::PersonalFm)
+    }
+
+    // 打开私人FM，没有在播放FM时开始播放
+    pub fn open_personal_fm(&mut self) {
+        self.push_navigation_stack(RouteId::PersonalFm, ActiveBlock::PersonalFm);
+        if !self.is_playing_fm() {
+            self.play_personal_fm();
+        }
+    }
+
+    // 以私人FM作为播放列表，播放FM的当前歌曲
+    pub fn play_personal_fm(&mut self) {
+        self.current_play_tracks = TrackTable {
+            context: Some(TrackTableContext::PersonalFm),
+            ..Default::default()
+        };
+        // 将下一曲播放队列置为空
+        self.next_play_tracks = vec![];
+        match self.personal_fm.current().cloned() {
+            Some(track) => self.play_fm_track(track),
+            None => self.personal_fm.play_when_loaded = true,
+        }
+        self.refill_personal_fm();
+    }
+
+    // 不喜欢FM的当前歌曲，移至垃圾桶并播放之后的歌曲
+    pub fn trash_fm_track(&mut self) {
+        let track_id = match self.personal_fm.current() {
+            Some(track) => track.id,
+            None => return,
+        };
+        self.dispatch(IoEvent::FmTrash(track_id));
+        self.disliked_track_ids.insert(track_id);
+        let next = self.personal_fm.trash_current();
+        if self.is_playing_fm() {
+            match next {
+                Some(track) => self.play_fm_track(track),
+                None => self.personal_fm.play_when_loaded = true,
+            }
+        }
+        self.refill_personal_fm();
+    }
+
+    fn fm_next_or_prev(&mut self, state: ToggleState) {
+        let track = match state {
+            ToggleState::Next => self.personal_fm.next(),
+            ToggleState::Prev => self.personal_fm.prev(),
+        };
+        match track {
+            Some(track) => self.play_fm_track(track),
+            // 待播放的歌曲已用完，获取到后继续播放
+            None if matches!(state, ToggleState::Next) => self.personal_fm.play_when_loaded = true,
+            None => {}
+        }
+        self.refill_personal_fm();
+    }
+
+    fn play_fm_track(&mut self, track: Track) {
+        let id = track.id;
+        self.dispatch(IoEvent::StartPlayback(track));
+        self.re_render_lyric(id);
+    }
+
+    // 待播放的歌曲不足时获取更多
+    fn refill_personal_fm(&mut self) {
+        if self.personal_fm.needs_refill() {
+            self.personal_fm.is_fetching = true;
+            self.dispatch(IoEvent::GetPersonalFm);
+        }
+    }
+
+    // 登录过期后重置登录状态，保留上次使用的国家码
+    pub fn reset_login(&mut self) {
+        let country_code = self.login_info.country_code.clone();
+        self.login_info = LoginInfo {
+            country_code,
+            ..Default::default()
+        };
+        self.clear_user_data();
+        self.is_session_expired = false;
+        self.is_logout = false;
+        self.set_current_route_state(Some(ActiveBlock::PhoneBlock), Some(ActiveBlock::PhoneBlock));
+    }
+
+    // 扫码登录时定时查询扫码状态
+    pub fn update_login_on_tick(&mut self) {
+        if self.login_info.login_mode != LoginMode::QrCode {
+            return;
+        }
+        if let Some(qr_login) = &mut self.login_info.qr_login {
+            let is_pending = matches!(
+                qr_login.state,
+                QrLoginState::Waiting | QrLoginState::Scanned
+            );
+            if is_pending && qr_login.last_check.elapsed() >= QR_LOGIN_CHECK_INTERVAL {
+                qr_login.last_check = Instant::now();
+                let key = qr_login.key.clone();
+                self.dispatch(IoEvent::CheckLoginQrCode(key));
+            }
+        }
+    }
+
     pub fn update_on_tick(&mut self) {
         if let Some(CurrentlyPlaybackContext {
             item: Some(item),
@@ -323,13 +526,18 @@ impl App {
 
     pub fn toggle_track(&mut self, track: Track, state: ToggleState) {
         if let Some(context) = &self.current_playback_context {
+            // 私人FM没有列表循环和随机播放，除单曲循环外都播放下一首
+            if self.is_playing_fm() && context.repeat_state != RepeatState::Track {
+                self.fm_next_or_prev(state);
+                return;
+            }
             match context.repeat_state {
                 RepeatState::Track => {
                     let id = track.id;
                     self.dispatch(IoEvent::StartPlayback(track));
                     self.re_render_lyric(id);
                 }
-                RepeatState::Context => {
+                RepeatState::Context | RepeatState::Intelligence => {
                     self.next_or_prev_track(state);
                 }
                 RepeatState::Shuffle => {
@@ -346,6 +554,7 @@ impl App {
                         }
                         let next_index =
                             App::next_index(&list.tracks, Some(current_play_track_index), state);
+                        self.prefetch_play_tracks(next_index);
 
                         let track = list.tracks.get(next_index.to_owned()).unwrap().to_owned();
                         let id = track.id;
@@ -366,6 +575,10 @@ impl App {
     }
 
     pub fn next_or_prev_track(&mut self, state: ToggleState) {
+        if self.is_playing_fm() {
+            self.fm_next_or_prev(state);
+            return;
+        }
         let next_tracks = self.next_play_tracks.clone();
         if !next_tracks.is_empty() {
             match state {
@@ -426,6 +639,7 @@ impl App {
             next_index = App::next_index(&list.tracks, Some(list.selected_index), state);
         }
         list.selected_index = next_index;
+        self.prefetch_play_tracks(next_index);
 
         let track = list.tracks.get(next_index.to_owned()).unwrap().to_owned();
         let id = track.id;
@@ -433,6 +647,109 @@ impl App {
         self.re_render_lyric(id);
     }
 
+    // 播放位置接近已加载歌曲的末尾时预加载歌单的下一页
+    fn prefetch_play_tracks(&mut self, index: usize) {
+        if let Some((playlist_id, offset, track_ids)) = self.current_play_tracks.next_page(index) {
+            self.dispatch(IoEvent::GetPlaylistTracksPage(
+                playlist_id,
+                offset,
+                track_ids,
+                false,
+            ));
+        }
+        self.refill_intelligence(index);
+        self.refill_radio(index);
+    }
+
+    /// 打开发现歌单，首次打开时获取分类和第一页歌单
+    pub fn open_discover(&mut self) {
+        if self.discover.categories.len() <= 1 {
+            self.dispatch(IoEvent::GetPlaylistCategories);
+        }
+        if self.discover.playlists.is_empty() {
+            self.load_discover_playlists();
+        }
+        self.push_navigation_stack(RouteId::Discover, ActiveBlock::DiscoverCategories);
+    }
+
+    /// 重新获取当前分类和排序的歌单
+    pub fn load_discover_playlists(&mut self) {
+        let (category, order, offset, before) = self.discover.reset();
+        self.dispatch(IoEvent::GetDiscoverPlaylists(
+            category, order, offset, before,
+        ));
+    }
+
+    /// 切换歌单排序：最热、最新、精品
+    pub fn toggle_discover_order(&mut self) {
+        self.discover.toggle_order();
+        self.load_discover_playlists();
+    }
+
+    // 选中位置接近已加载歌单的末尾时加载下一页
+    pub fn prefetch_discover_playlists(&mut self) {
+        if let Some((category, order, offset, before)) =
+            self.discover.next_page(self.discover.selected_index)
+        {
+            self.discover.is_fetching = true;
+            self.dispatch(IoEvent::GetDiscoverPlaylists(
+                category, order, offset, before,
+            ));
+        }
+    }
+
+    /// 以该歌曲开始相似歌曲电台，播放列表随播放不断加入相似歌曲
+    pub fn start_radio(&mut self, track: Track) {
+        self.radio = Radio::new(track.id);
+        self.current_play_tracks = TrackTable {
+            tracks: vec![track.clone()],
+            context: Some(TrackTableContext::Radio),
+            ..Default::default()
+        };
+        // 将下一曲播放队列置为空
+        self.next_play_tracks = vec![];
+        self.track_table = self.current_play_tracks.clone();
+        self.title = format!("{} 的相似歌曲电台", track.name);
+        self.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
+        self.dispatch(IoEvent::GetSimiSongs(track.id));
+        self.dispatch(IoEvent::StartPlayback(track));
+    }
+
+    /// 添加以seed_id获取到的相似歌曲，跳过已有的和不喜欢的歌曲
+    pub fn extend_radio(&mut self, seed_id: usize, tracks: Vec<Track>) {
+        // 已开始新的电台或不再播放电台时丢弃
+        if seed_id != self.radio.seed_id
+            || self.current_play_tracks.context != Some(TrackTableContext::Radio)
+        {
+            return;
+        }
+        let tracks = filter_new_tracks(
+            tracks,
+            &self.current_play_tracks.tracks,
+            &self.disliked_track_ids,
+        );
+        self.current_play_tracks.tracks.extend(tracks);
+        if self.track_table.context == Some(TrackTableContext::Radio) {
+            self.track_table.tracks = self.current_play_tracks.tracks.clone();
+        }
+    }
+
+    // 电台的歌曲快播放完时，获取已播放歌曲的相似歌曲
+    fn refill_radio(&mut self, index: usize) {
+        if self.current_play_tracks.context != Some(TrackTableContext::Radio) {
+            return;
+        }
+        if let Some(track_id) = self
+            .radio
+            .next_seed(&self.current_play_tracks.tracks, index)
+        {
+            self.radio.seeded_ids.insert(track_id);
+            self.radio.seed_id = track_id;
+            self.radio.is_fetching = true;
+            self.dispatch(IoEvent::GetSimiSongs(track_id));
+        }
+    }
+
     #[allow(unused)]
     fn re_render_lyric(&mut self, track_id: usize) {
         let current_route = self.get_current_route();
@@ -444,6 +761,15 @@ impl App {
     }
 
     pub fn shuffle(&mut self) {
+        // 随机播放需要从整个歌单中选取，加载歌单剩余的全部歌曲
+        if let Some((playlist_id, offset, track_ids)) = self.current_play_tracks.remaining_pages() {
+            self.dispatch(IoEvent::GetPlaylistTracksPage(
+                playlist_id,
+                offset,
+                track_ids,
+                false,
+            ));
+        }
         let mut list = self.current_play_tracks.clone();
         if list.tracks.is_empty().not() {
             let mut rng = rand::thread_rng();
@@ -463,14 +789,154 @@ impl App {
             let next_repeat_state = match context.repeat_state {
                 RepeatState::Context => RepeatState::Track,
                 RepeatState::Track => RepeatState::Shuffle,
+                // 心动模式只能在播放我喜欢歌单时开启
+                RepeatState::Shuffle if self.can_start_intelligence() => RepeatState::Intelligence,
                 RepeatState::Shuffle => RepeatState::Off,
+                RepeatState::Intelligence => RepeatState::Off,
                 RepeatState::Off => RepeatState::Context,
             };
+            match (context.repeat_state, next_repeat_state) {
+                (_, RepeatState::Intelligence) => self.start_intelligence(),
+                (RepeatState::Intelligence, _) => self.stop_intelligence(),
+                _ => {}
+            }
             context.repeat_state = next_repeat_state;
             self.current_playback_context = Some(context);
         }
     }
 
+    pub fn is_intelligence_mode(&self) -> bool {
+        self.current_playback_context
+            .as_ref()
+            .map(|context| context.repeat_state == RepeatState::Intelligence)
+            .unwrap_or(false)
+    }
+
+    fn can_start_intelligence(&self) -> bool {
+        let playlist_id = self
+            .current_play_tracks
+            .pagination
+            .as_ref()
+            .map(|pagination| pagination.playlist_id);
+        self.my_like_playlist_id != 0 && playlist_id == Some(self.my_like_playlist_id)
+    }
+
+    // 以当前播放的歌曲开始心动模式
+    fn start_intelligence(&mut self) {
+        let track_id = match self
+            .current_playback_context
+            .as_ref()
+            .and_then(|context| context.item.as_ref())
+        {
+            Some(track) => track.id,
+            None => return,
+        };
+        self.intelligence = Intelligence {
+            playlist_id: self.my_like_playlist_id,
+            seed_id: track_id,
+            origin: Some(self.current_play_tracks.clone()),
+            is_fetching: true,
+            ..Default::default()
+        };
+        self.dispatch(IoEvent::GetIntelligenceList(
+            track_id,
+            self.my_like_playlist_id,
+        ));
+    }
+
+    // 退出心动模式，恢复原来的播放列表
+    fn stop_intelligence(&mut self) {
+        let intelligence = std::mem::take(&mut self.intelligence);
+        if self.current_play_tracks.context == Some(TrackTableContext::Intelligence) {
+            if let Some(origin) = intelligence.origin {
+                self.current_play_tracks = origin;
+            }
+        }
+    }
+
+    // 心动模式的状态已失效，退回列表循环
+    fn exit_intelligence(&mut self) {
+        self.intelligence = Intelligence::default();
+        if let Some(context) = &mut self.current_playback_context {
+            if context.repeat_state == RepeatState::Intelligence {
+                context.repeat_state = RepeatState::Context;
+            }
+        }
+    }
+
+    /// 添加以seed_id请求到的心动模式歌曲，首次返回时以当前播放的歌曲开始新的播放列表
+    pub fn extend_intelligence_list(&mut self, seed_id: usize, items: Vec<(Track, bool)>) {
+        if !self.is_intelligence_mode() || seed_id != self.intelligence.seed_id {
+            return;
+        }
+        let is_start = self.current_play_tracks.context != Some(TrackTableContext::Intelligence);
+        if is_start {
+            // 请求期间已切换到其他歌单或歌曲时不再开始
+            let current_id = self
+                .current_playback_context
+                .as_ref()
+                .and_then(|context| context.item.as_ref())
+                .map(|track| track.id);
+            if !self.can_start_intelligence() || current_id != Some(seed_id) {
+                self.exit_intelligence();
+                return;
+            }
+            let current = self
+                .current_playback_context
+                .as_ref()
+                .and_then(|context| context.item.clone());
+            self.current_play_tracks = TrackTable {
+                tracks: current.into_iter().collect(),
+                context: Some(TrackTableContext::Intelligence),
+                ..Default::default()
+            };
+            // 将下一曲播放队列置为空
+            self.next_play_tracks = vec![];
+        }
+        for (track, recommended) in items {
+            if self
+                .current_play_tracks
+                .tracks
+                .iter()
+                .any(|t| t.id == track.id)
+            {
+                continue;
+            }
+            if recommended {
+                self.intelligence.recommended_ids.insert(track.id);
+            }
+            self.current_play_tracks.tracks.push(track);
+        }
+        if is_start {
+            self.track_table = self.current_play_tracks.clone();
+            self.title = String::from("心动模式");
+            self.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
+        } else if self.track_table.context == Some(TrackTableContext::Intelligence) {
+            self.track_table.tracks = self.current_play_tracks.tracks.clone();
+        }
+    }
+
+    // 心动模式的歌曲快播放完时，以最后一首获取更多歌曲
+    fn refill_intelligence(&mut self, index: usize) {
+        let tracks = &self.current_play_tracks;
+        if !self.is_intelligence_mode()
+            || self.intelligence.is_fetching
+            || tracks.context != Some(TrackTableContext::Intelligence)
+            || index + INTELLIGENCE_REFILL_THRESHOLD < tracks.tracks.len()
+        {
+            return;
+        }
+        if let Some(track) = tracks.tracks.last() {
+            let track_id = track.id;
+            self.intelligence.seed_id = track_id;
+            self.intelligence.is_fetching = true;
+            self.dispatch(IoEvent::GetIntelligenceList(
+                track_id,
+                self.intelligence.playlist_id,
+            ));
+        }
+    }
+
     pub fn decrease_volume(&mut self) {
         self.dispatch(IoEvent::DecreaseVolume);
     }
@@ -586,6 +1052,10 @@ impl Default for App {
             seek_ms: None,
             library: Library { selected_index: 0 },
             api_error: String::new(),
+            is_session_expired: false,
+            is_logout: false,
+            accounts: vec![],
+            accounts_selected_index: 0,
             dialog: None,
             user: None,
             track_table: Default::default(),
@@ -595,6 +1065,10 @@ impl Default for App {
             volume: 1f32,
             title: String::from("歌曲列表"),
             current_play_tracks: Default::default(),
+            personal_fm: Default::default(),
+            intelligence: Default::default(),
+            radio: Default::default(),
+            disliked_track_ids: HashSet::new(),
             liked_track_ids_set: HashSet::new(),
             lyric_index: 0,
             lyric: None,
@@ -602,6 +1076,9 @@ impl Default for App {
             artists: vec![],
             artist_sub_ids_set: HashSet::new(),
             artists_selected_index: 0,
+            toplists: vec![],
+            toplists_selected_index: 0,
+            discover: Default::default(),
             artist_detail: None,
             album_detail: None,
             login_info: Default::default(),
@@ -616,7 +1093,186 @@ impl Default for App {
 
 #[test]
 pub fn test() {
+    use openssl::hash::{hash, MessageDigest};
+
     let str = "123456";
     let string1 = hex::encode(hash(MessageDigest::md5(), str.as_bytes()).unwrap());
     println!("{:?}", string1);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{channel, Receiver};
+
+    use std::time::Instant;
+
+    use super::{ActiveBlock, App, RouteId, QR_LOGIN_CHECK_INTERVAL};
+    use crate::config::user_config::UserConfig;
+    use crate::event::IoEvent;
+    use crate::model::context::{CurrentlyPlaybackContext, TrackTableContext};
+    use crate::model::enums::RepeatState;
+    use crate::model::login::{LoginMode, QrLogin, QrLoginState};
+    use crate::model::table::{TrackPagination, TrackTable};
+    use crate::model::track::track;
+
+    const LIKE_PLAYLIST_ID: usize = 10;
+
+    fn playlist(playlist_id: usize, ids: &[usize]) -> TrackTable {
+        TrackTable {
+            tracks: ids.iter().map(|&id| track(id)).collect(),
+            context: Some(TrackTableContext::MyPlaylists),
+            pagination: Some(TrackPagination {
+                playlist_id,
+                track_ids: ids.to_vec(),
+                loaded: ids.len(),
+                requested: ids.len(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    // 正在随机播放我喜欢歌单的第一首歌曲
+    fn playing_like_playlist() -> (App, Receiver<IoEvent>) {
+        let (tx, rx) = channel();
+        let mut app = App::new(tx, UserConfig::new());
+        app.my_like_playlist_id = LIKE_PLAYLIST_ID;
+        app.current_play_tracks = playlist(LIKE_PLAYLIST_ID, &[1, 2]);
+        app.current_playback_context = Some(CurrentlyPlaybackContext {
+            repeat_state: RepeatState::Shuffle,
+            ..CurrentlyPlaybackContext::new(Some(track(1)))
+        });
+        (app, rx)
+    }
+
+    fn play_ids(app: &App) -> Vec<usize> {
+        app.current_play_tracks
+            .tracks
+            .iter()
+            .map(|t| t.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_start_intelligence() {
+        let (mut app, rx) = playing_like_playlist();
+        app.toggle_play_state();
+        assert!(app.is_intelligence_mode());
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(IoEvent::GetIntelligenceList(1, LIKE_PLAYLIST_ID))
+        ));
+
+        app.extend_intelligence_list(
+            1,
+            vec![(track(3), true), (track(1), false), (track(4), false)],
+        );
+        assert_eq!(play_ids(&app), vec![1, 3, 4]);
+        assert!(app.intelligence.recommended_ids.contains(&3));
+        assert_eq!(app.get_current_route().id, RouteId::TrackTable);
+        assert_eq!(
+            app.get_current_route().active_block,
+            ActiveBlock::TrackTable
+        );
+
+        // 退出后恢复原来的播放列表
+        app.toggle_play_state();
+        assert_eq!(play_ids(&app), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_drop_stale_intelligence_list() {
+        let (mut app, _rx) = playing_like_playlist();
+        app.toggle_play_state();
+        // 请求返回前已开始播放其他歌单
+        app.current_play_tracks = playlist(20, &[5, 6]);
+        app.extend_intelligence_list(1, vec![(track(3), true)]);
+        assert_eq!(play_ids(&app), vec![5, 6]);
+        assert_eq!(app.get_current_route().id, RouteId::Home);
+        assert!(!app.is_intelligence_mode());
+
+        // 种子歌曲不是最近一次请求的歌曲
+        let (mut app, _rx) = playing_like_playlist();
+        app.toggle_play_state();
+        app.extend_intelligence_list(2, vec![(track(3), true)]);
+        assert_eq!(play_ids(&app), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_refill_intelligence() {
+        let (mut app, rx) = playing_like_playlist();
+        app.toggle_play_state();
+        app.extend_intelligence_list(1, vec![(track(3), true), (track(4), false)]);
+        app.intelligence.is_fetching = false;
+        while rx.try_recv().is_ok() {}
+
+        app.prefetch_play_tracks(1);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(IoEvent::GetIntelligenceList(4, LIKE_PLAYLIST_ID))
+        ));
+        assert_eq!(app.intelligence.seed_id, 4);
+        // 正在获取时不重复请求
+        app.prefetch_play_tracks(2);
+        assert!(rx.try_recv().is_err());
+
+        app.extend_intelligence_list(4, vec![(track(5), false)]);
+        assert_eq!(play_ids(&app), vec![1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_shuffle_loads_remaining_pages() {
+        let (mut app, rx) = playing_like_playlist();
+        let pagination = app.current_play_tracks.pagination.as_mut().unwrap();
+        pagination.track_ids = vec![1, 2, 3, 4];
+        app.shuffle();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(IoEvent::GetPlaylistTracksPage(LIKE_PLAYLIST_ID, 2, ids, false)) if ids == [3, 4]
+        ));
+        // 剩余歌曲已在加载中，不重复请求
+        app.shuffle();
+        assert!(!rx
+            .try_iter()
+            .any(|e| matches!(e, IoEvent::GetPlaylistTracksPage(..))));
+    }
+
+    #[test]
+    fn test_qr_login_polling() {
+        let (tx, rx) = channel();
+        let mut app = App::new(tx, UserConfig::new());
+        app.login_info.login_mode = LoginMode::QrCode;
+        let mut qr_login = QrLogin::new("key".to_string(), String::new());
+        qr_login.last_check = Instant::now() - QR_LOGIN_CHECK_INTERVAL;
+        app.login_info.qr_login = Some(qr_login);
+        app.update_login_on_tick();
+        assert!(matches!(rx.try_recv(), Ok(IoEvent::CheckLoginQrCode(key)) if key == "key"));
+
+        // 查询失败后不再轮询
+        let qr_login = app.login_info.qr_login.as_mut().unwrap();
+        qr_login.state = QrLoginState::Failed;
+        qr_login.last_check = Instant::now() - QR_LOGIN_CHECK_INTERVAL;
+        app.update_login_on_tick();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_drop_stale_radio_tracks() {
+        let (mut app, _rx) = playing_like_playlist();
+        app.start_radio(track(1));
+        app.start_radio(track(2));
+        // 上一个电台的相似歌曲晚于新电台返回
+        app.extend_radio(1, vec![track(3)]);
+        assert_eq!(play_ids(&app), vec![2]);
+
+        app.extend_radio(2, vec![track(2), track(4)]);
+        assert_eq!(play_ids(&app), vec![2, 4]);
+    }
+
+    #[test]
+    fn test_clear_user_data_exits_intelligence() {
+        let (mut app, _rx) = playing_like_playlist();
+        app.toggle_play_state();
+        app.clear_user_data();
+        assert!(!app.is_intelligence_mode());
+    }
+}
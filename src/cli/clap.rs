@@ -40,6 +40,33 @@ impl ClapApplication {
                         .long("config")
                         .takes_value(true)
                         .help("netease-cloud-music-tui config path"),
+                )
+                .arg(
+                    Arg::with_name("cookie")
+                        .long("cookie")
+                        .takes_value(true)
+                        .value_name("COOKIE")
+                        .help("login with a cookie string, MUSIC_U value or Netscape cookies.txt path"),
+                )
+                .arg(
+                    Arg::with_name("record")
+                        .long("record")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .conflicts_with("replay")
+                        .help("record API requests and responses to DIR, also set by RS_MUSIC_TUI_RECORD"),
+                )
+                .arg(
+                    Arg::with_name("replay")
+                        .long("replay")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .help("replay API responses recorded in DIR without network access, also set by RS_MUSIC_TUI_REPLAY"),
+                )
+                .arg(
+                    Arg::with_name("replay-fallback")
+                        .long("replay-fallback")
+                        .help("when replaying, answer a request without an exact recording with any recording of the same API, also set by RS_MUSIC_TUI_REPLAY_FALLBACK"),
                 ),
         }
     }
@@ -26,6 +26,13 @@ pub struct KeyBindings {
     pub show_lyric: Key,
     pub show_playbar_lyric: Key,
     pub reset_play: Key,
+    pub manage_accounts: Key,
+    pub fm_trash: Key,
+    pub start_radio: Key,
+    pub toggle_playlist_order: Key,
+    pub add_account: Key,
+    pub remove_account: Key,
+    pub logout: Key,
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -53,4 +60,11 @@ pub struct KeyBindingsString {
     pub show_lyric: Option<String>,
     pub show_playbar_lyric: Option<String>,
     pub reset_play: Option<String>,
+    pub manage_accounts: Option<String>,
+    pub fm_trash: Option<String>,
+    pub start_radio: Option<String>,
+    pub toggle_playlist_order: Option<String>,
+    pub add_account: Option<String>,
+    pub remove_account: Option<String>,
+    pub logout: Option<String>,
 }
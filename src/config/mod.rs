@@ -1,4 +1,5 @@
 pub(crate) mod behavior;
 pub(crate) mod keybinds;
+pub(crate) mod network;
 pub(crate) mod theme;
 pub(crate) mod user_config;
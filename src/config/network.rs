@@ -0,0 +1,190 @@
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::http::retry::RetryPolicy;
+
+const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+const DEFAULT_CONNECT_TIMEOUT_SECONDS: u64 = 10;
+const DEFAULT_RATE_LIMIT: u32 = 10;
+const DEFAULT_RATE_LIMIT_BURST: u32 = 20;
+const PROXY_SCHEMES: [&str; 4] = ["http://", "https://", "socks5://", "socks5h://"];
+
+static NETWORK_CONFIG: OnceLock<NetworkConfig> = OnceLock::new();
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    // 代理地址，同时用于接口请求和音频下载，如 socks5://127.0.0.1:1080
+    pub proxy: Option<String>,
+    // 仅用于接口请求的代理，设置为空字符串表示接口请求不走代理
+    pub api_proxy: Option<String>,
+    // 仅用于音频下载的代理，设置为空字符串表示音频下载不走代理
+    pub audio_proxy: Option<String>,
+    // 请求头X-Real-IP，用于解除部分地区的版权限制
+    pub real_ip: Option<String>,
+    // 接口请求超时秒数
+    pub timeout_seconds: Option<u64>,
+    // 建立连接超时秒数，同时用于音频下载
+    pub connect_timeout_seconds: Option<u64>,
+    // 接口请求失败后的最大重试次数，0表示不重试
+    pub max_retries: Option<u32>,
+    // 第一次重试前的等待毫秒数，之后每次翻倍
+    pub retry_base_delay_milliseconds: Option<u64>,
+    // 每秒最多发送的接口请求数
+    pub rate_limit: Option<u32>,
+    // 短时间内允许突发的接口请求数
+    pub rate_limit_burst: Option<u32>,
+    // 接口请求发往的地址，如本地的缓存代理或模拟服务 http://127.0.0.1:3000，
+    // 接口路径和weapi/eapi加密不变，该服务需原样转发或接受网易云的加密请求；
+    // NeteaseCloudMusicApi等使用自己路由和参数的服务不支持
+    pub api_base_url: Option<String>,
+}
+
+impl NetworkConfig {
+    pub fn api_proxy(&self) -> Option<&str> {
+        resolve_proxy(&self.api_proxy, &self.proxy)
+    }
+
+    pub fn audio_proxy(&self) -> Option<&str> {
+        resolve_proxy(&self.audio_proxy, &self.proxy)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS))
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.connect_timeout_seconds
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECONDS),
+        )
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        let default = RetryPolicy::default();
+        RetryPolicy {
+            max_retries: self.max_retries.unwrap_or(default.max_retries),
+            base_delay: self
+                .retry_base_delay_milliseconds
+                .map(Duration::from_millis)
+                .unwrap_or(default.base_delay),
+            ..default
+        }
+    }
+
+    pub fn rate_limit(&self) -> u32 {
+        self.rate_limit.unwrap_or(DEFAULT_RATE_LIMIT)
+    }
+
+    pub fn rate_limit_burst(&self) -> u32 {
+        self.rate_limit_burst.unwrap_or(DEFAULT_RATE_LIMIT_BURST)
+    }
+
+    pub fn api_base_url(&self) -> Option<&str> {
+        self.api_base_url.as_deref().filter(|u| !u.is_empty())
+    }
+
+    pub fn real_ip(&self) -> Option<&str> {
+        self.real_ip.as_deref().filter(|ip| !ip.is_empty())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(url) = self.api_base_url() {
+            let valid = Url::parse(url)
+                .map(|u| u.scheme() == "http" || u.scheme() == "https")
+                .unwrap_or(false);
+            if !valid {
+                return Err(anyhow!(
+                    "Api base url must be a http:// or https:// url, is {}",
+                    url
+                ));
+            }
+        }
+        if let Some(real_ip) = self.real_ip() {
+            if real_ip.parse::<IpAddr>().is_err() {
+                return Err(anyhow!("Real ip must be an ip address, is {}", real_ip));
+            }
+        }
+        if self.rate_limit == Some(0) || self.rate_limit_burst == Some(0) {
+            return Err(anyhow!("Rate limit and burst must be greater than 0"));
+        }
+        for proxy in [&self.proxy, &self.api_proxy, &self.audio_proxy]
+            .into_iter()
+            .flatten()
+            .filter(|p| !p.is_empty())
+        {
+            if !PROXY_SCHEMES.iter().any(|s| proxy.starts_with(s)) {
+                return Err(anyhow!(
+                    "Proxy must start with one of {}, is {}",
+                    PROXY_SCHEMES.join(", "),
+                    proxy
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn resolve_proxy<'a>(proxy: &'a Option<String>, fallback: &'a Option<String>) -> Option<&'a str> {
+    proxy
+        .as_deref()
+        .or(fallback.as_deref())
+        .filter(|p| !p.is_empty())
+}
+
+/// 设置全局网络配置，需在创建网络客户端前调用
+pub fn init(config: NetworkConfig) {
+    NETWORK_CONFIG.set(config).ok();
+}
+
+pub fn get() -> NetworkConfig {
+    NETWORK_CONFIG.get().cloned().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NetworkConfig;
+
+    #[test]
+    fn test_validate() {
+        assert!(NetworkConfig::default().validate().is_ok());
+        let valid = NetworkConfig {
+            proxy: Some("socks5://127.0.0.1:1080".to_string()),
+            api_proxy: Some("".to_string()),
+            real_ip: Some("116.25.146.177".to_string()),
+            api_base_url: Some("http://127.0.0.1:3000".to_string()),
+            ..Default::default()
+        };
+        assert!(valid.validate().is_ok());
+
+        let invalid = [
+            NetworkConfig {
+                api_base_url: Some("127.0.0.1:3000".to_string()),
+                ..Default::default()
+            },
+            NetworkConfig {
+                rate_limit: Some(0),
+                ..Default::default()
+            },
+            NetworkConfig {
+                rate_limit_burst: Some(0),
+                ..Default::default()
+            },
+            NetworkConfig {
+                audio_proxy: Some("ftp://127.0.0.1".to_string()),
+                ..Default::default()
+            },
+            NetworkConfig {
+                real_ip: Some("116.25.146\n".to_string()),
+                ..Default::default()
+            },
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+}
@@ -8,6 +8,7 @@ use tui::style::Color;
 
 use crate::config::behavior::{BehaviorConfig, BehaviorConfigString};
 use crate::config::keybinds::{KeyBindings, KeyBindingsString};
+use crate::config::network::NetworkConfig;
 use crate::config::theme::{Theme, UserTheme};
 use crate::event::Key;
 
@@ -21,6 +22,7 @@ pub struct UserConfig {
     pub behavior: BehaviorConfig,
     pub theme: Theme,
     pub keys: KeyBindings,
+    pub network: NetworkConfig,
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -28,6 +30,7 @@ pub struct UserConfigString {
     keybindings: Option<KeyBindingsString>,
     behavior: Option<BehaviorConfigString>,
     theme: Option<UserTheme>,
+    network: Option<NetworkConfig>,
 }
 
 #[derive(Clone)]
@@ -65,7 +68,15 @@ impl UserConfig {
                 show_lyric: Key::Ctrl('l'),
                 show_playbar_lyric: Key::Ctrl('k'),
                 reset_play: Key::Char('R'),
+                manage_accounts: Key::Char('U'),
+                fm_trash: Key::Char('D'),
+                start_radio: Key::Char('O'),
+                toggle_playlist_order: Key::Char('o'),
+                add_account: Key::Char('a'),
+                remove_account: Key::Char('d'),
+                logout: Key::Char('X'),
             },
+            network: NetworkConfig::default(),
         }
     }
 
@@ -130,6 +141,10 @@ impl UserConfig {
             if let Some(theme) = config_yml.theme {
                 self.load_theme(theme)?;
             }
+            if let Some(network) = config_yml.network {
+                network.validate()?;
+                self.network = network;
+            }
             Ok(())
         } else {
             Ok(())
@@ -168,6 +183,13 @@ impl UserConfig {
         to_keys!(show_lyric);
         to_keys!(show_playbar_lyric);
         to_keys!(reset_play);
+        to_keys!(manage_accounts);
+        to_keys!(fm_trash);
+        to_keys!(start_radio);
+        to_keys!(toggle_playlist_order);
+        to_keys!(add_account);
+        to_keys!(remove_account);
+        to_keys!(logout);
 
         Ok(())
     }
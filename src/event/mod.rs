@@ -1,7 +1,8 @@
 pub use self::events::{Event, Events};
 pub use self::key::Key;
 use crate::model::album::Album;
-use crate::model::login::LoginForm;
+use crate::model::discover::PlaylistOrder;
+use crate::model::login::{CaptchaLoginForm, EmailLoginForm, LoginForm};
 use crate::model::track::Track;
 
 mod events;
@@ -14,6 +15,16 @@ pub enum IoEvent {
     GetPlaylists,
     GetUser,
     GetPlaylistTracks(usize),
+    // 获取排行榜列表
+    GetToplists,
+    // 获取歌单分类和精品歌单标签
+    GetPlaylistCategories,
+    // 按分类获取一页歌单(分类, 排序, 偏移, 精品歌单的起始时间)
+    GetDiscoverPlaylists(String, PlaylistOrder, usize, u64),
+    // 获取排行榜歌曲(榜单id, 榜单名)
+    GetToplistTracks(usize, String),
+    // 分页加载歌单歌曲(歌单id, 起始位置, 歌曲id, 加载后是否选中最后一首)
+    GetPlaylistTracksPage(usize, usize, Vec<usize>, bool),
     // CurrentUserSavedTracksContains(Vec<String>),
     StartPlayback(Track),
     // GetCurrentPlayback(Track),
@@ -24,7 +35,6 @@ pub enum IoEvent {
     GetRecommendTracks,
     DecreaseVolume,
     IncreaseVolume,
-    GetLikeList,
     GetLyric(usize, bool),
     // 喜欢or不喜欢歌曲
     ToggleLikeTrack(usize),
@@ -39,7 +49,33 @@ pub enum IoEvent {
     // 收藏or取消收藏歌手
     ToggleSubscribeArtist(usize),
     Login(LoginForm),
+    // 发送短信验证码(国家码, 手机号)
+    SendCaptcha(String, String),
+    // 获取国家/地区列表
+    GetCountries,
+    // 短信验证码登录
+    CaptchaLogin(CaptchaLoginForm),
+    // 邮箱登录
+    EmailLogin(EmailLoginForm),
+    // 导入cookie登录
+    CookieLogin(String),
+    // 获取登录二维码
+    GetLoginQrCode,
+    // 查询扫码登录状态
+    CheckLoginQrCode(String),
     AddToQueue(Track),
     // 重置当前播放
-    ResetPlay
+    ResetPlay,
+    // 获取私人FM的歌曲
+    GetPersonalFm,
+    // 私人FM中不喜欢的歌曲移至垃圾桶
+    FmTrash(usize),
+    // 获取心动模式的歌曲(歌曲id, 我喜欢歌单id)
+    GetIntelligenceList(usize, usize),
+    // 获取相似歌曲加入电台
+    GetSimiSongs(usize),
+    // 退出登录
+    Logout,
+    // 切换到已保存的账号
+    SwitchAccount(usize),
 }
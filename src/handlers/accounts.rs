@@ -0,0 +1,73 @@
+use crate::app::{ActiveBlock, RouteId};
+use crate::event::Key;
+use crate::handlers::common_key_events;
+use crate::model::context::DialogContext;
+use crate::model::dialog::Dialog;
+use crate::{App, IoEvent};
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            let next_index = common_key_events::on_down_press_handler(
+                &app.accounts,
+                Some(app.accounts_selected_index),
+            );
+            app.accounts_selected_index = next_index;
+        }
+        k if common_key_events::up_event(k) => {
+            let next_index = common_key_events::on_up_press_handler(
+                &app.accounts,
+                Some(app.accounts_selected_index),
+            );
+            app.accounts_selected_index = next_index;
+        }
+        Key::Enter => {
+            if let Some(account) = app.accounts.get(app.accounts_selected_index) {
+                let user_id = account.user_id;
+                if !is_current_user(app, user_id) {
+                    app.dispatch(IoEvent::SwitchAccount(user_id));
+                }
+            }
+        }
+        // 添加账号，当前账号已保存，回到登录页登录新账号
+        k if k == app.user_config.keys.add_account => {
+            app.is_logout = true;
+        }
+        // 删除已保存的其他账号
+        k if k == app.user_config.keys.remove_account => {
+            if let Some(account) = app.accounts.get(app.accounts_selected_index) {
+                if is_current_user(app, account.user_id) {
+                    return;
+                }
+                app.dialog = Some(Dialog {
+                    tips: "确定要删除账号：".to_string(),
+                    item_name: account.nickname.clone(),
+                    confirm: false,
+                });
+                app.push_navigation_stack(
+                    RouteId::Dialog,
+                    ActiveBlock::Dialog(DialogContext::RemoveAccount),
+                );
+            }
+        }
+        k if k == app.user_config.keys.logout => {
+            if let Some(user) = &app.user {
+                app.dialog = Some(Dialog {
+                    tips: "确定要退出登录：".to_string(),
+                    item_name: user.nickname.clone(),
+                    confirm: false,
+                });
+                app.push_navigation_stack(
+                    RouteId::Dialog,
+                    ActiveBlock::Dialog(DialogContext::Logout),
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn is_current_user(app: &App, user_id: usize) -> bool {
+    app.user.as_ref().map(|u| u.user_id) == Some(user_id)
+}
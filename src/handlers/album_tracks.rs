@@ -35,6 +35,7 @@ pub fn handler(key: Key, app: &mut App) {
                         tracks: album_detail.tracks.clone(),
                         selected_index: album_detail.selected_track_index,
                         context: Some(TrackTableContext::AlbumDetail),
+                        ..Default::default()
                     };
                     // 将下一曲播放队列置为空
                     app.next_play_tracks = vec![];
@@ -51,6 +52,17 @@ pub fn handler(key: Key, app: &mut App) {
                 app.dispatch(IoEvent::ToggleLikeTrack(id));
             };
         }
+        // 以选中歌曲开始相似歌曲电台
+        k if k == app.user_config.keys.start_radio => {
+            let track = app
+                .album_detail
+                .as_ref()
+                .and_then(|album_detail| album_detail.tracks.get(album_detail.selected_track_index))
+                .cloned();
+            if let Some(track) = track {
+                app.start_radio(track);
+            }
+        }
         // 加入下一曲播放队列
         k if k == app.user_config.keys.add_item_to_queue => {
             let (selected_index, tracks) = (
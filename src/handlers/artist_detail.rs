@@ -91,6 +91,11 @@ pub fn handler(key: Key, app: &mut App) {
             k if k == app.user_config.keys.add_item_to_queue => {
                 add_to_queue(app);
             }
+            k if k == app.user_config.keys.start_radio
+                && artist.artist_detail_selected_block == ArtistBlock::Tracks =>
+            {
+                start_radio(app);
+            }
             _ => {}
         };
     }
@@ -107,6 +112,17 @@ fn add_to_queue(app: &mut App) {
     };
 }
 
+fn start_radio(app: &mut App) {
+    let track = app
+        .artist_detail
+        .as_ref()
+        .and_then(|artist| artist.tracks.get(artist.selected_track_index))
+        .cloned();
+    if let Some(track) = track {
+        app.start_radio(track);
+    }
+}
+
 fn handle_toggle_subscribe_artist_event(app: &mut App) {
     if let Some(artist) = &mut app.artist_detail {
         let selected_index = artist.selected_simi_artist_index;
@@ -292,6 +308,7 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
                         tracks: artist.tracks.clone(),
                         selected_index,
                         context: Some(TrackTableContext::ArtistDetail),
+                        ..Default::default()
                     };
                     // 将下一曲播放队列置为空
                     app.next_play_tracks = vec![];
@@ -156,6 +156,22 @@ pub fn handle_right_event(app: &mut App) {
                     Some(ActiveBlock::AlbumTracks),
                     Some(ActiveBlock::AlbumTracks),
                 ),
+                RouteId::Accounts => app.set_current_route_state(
+                    Some(ActiveBlock::Accounts),
+                    Some(ActiveBlock::Accounts),
+                ),
+                RouteId::PersonalFm => app.set_current_route_state(
+                    Some(ActiveBlock::PersonalFm),
+                    Some(ActiveBlock::PersonalFm),
+                ),
+                RouteId::Toplists => app.set_current_route_state(
+                    Some(ActiveBlock::Toplists),
+                    Some(ActiveBlock::Toplists),
+                ),
+                RouteId::Discover => app.set_current_route_state(
+                    Some(ActiveBlock::DiscoverCategories),
+                    Some(ActiveBlock::DiscoverCategories),
+                ),
                 RouteId::Lyric => {
                     app.set_current_route_state(Some(ActiveBlock::Lyric), Some(ActiveBlock::Lyric));
                 }
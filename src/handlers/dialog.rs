@@ -1,5 +1,7 @@
 use crate::app::ActiveBlock;
 use crate::event::Key;
+use crate::handlers::accounts::is_current_user;
+use crate::http::account_manager;
 use crate::model::context::DialogContext;
 use crate::model::dialog::Dialog as OtherDialog;
 use crate::{App, IoEvent};
@@ -15,6 +17,8 @@ pub fn handler(key: Key, app: &mut App) {
                                 DialogContext::Playlist => {}
                                 DialogContext::SubPlaylist => handle_sub_playlist_dialog(app),
                                 DialogContext::PlaylistSearch => {}
+                                DialogContext::Logout => app.dispatch(IoEvent::Logout),
+                                DialogContext::RemoveAccount => handle_remove_account_dialog(app),
                             }
                         }
                     }
@@ -44,3 +48,22 @@ fn handle_sub_playlist_dialog(app: &mut App) {
         app.dispatch(IoEvent::ToggleSubscribePlaylist(selected_id))
     }
 }
+
+fn handle_remove_account_dialog(app: &mut App) {
+    if let Some(account) = app.accounts.get(app.accounts_selected_index) {
+        let user_id = account.user_id;
+        if is_current_user(app, user_id) {
+            return;
+        }
+        match account_manager().and_then(|manager| {
+            manager.remove(user_id)?;
+            Ok(manager.accounts())
+        }) {
+            Ok(accounts) => {
+                app.accounts = accounts;
+                app.accounts_selected_index = 0;
+            }
+            Err(e) => app.handle_error(e),
+        }
+    }
+}
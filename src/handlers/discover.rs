@@ -0,0 +1,125 @@
+use crate::app::{ActiveBlock, App};
+use crate::event::{IoEvent, Key};
+use crate::handlers::common_key_events;
+use crate::handlers::common_key_events::KeyAction;
+
+pub fn category_handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::right_event(k) => focus_playlists(app),
+        k if common_key_events::down_event(k) => {
+            let next_index = common_key_events::on_down_press_handler(
+                app.discover.current_categories(),
+                Some(app.discover.category_index),
+            );
+            app.discover.category_index = next_index;
+        }
+        k if common_key_events::up_event(k) => {
+            let next_index = common_key_events::on_up_press_handler(
+                app.discover.current_categories(),
+                Some(app.discover.category_index),
+            );
+            app.discover.category_index = next_index;
+        }
+        k if common_key_events::high_event(k) => {
+            app.discover.category_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) => {
+            let next_index =
+                common_key_events::on_middle_press_handler(app.discover.current_categories());
+            app.discover.category_index = next_index;
+        }
+        k if common_key_events::low_event(k) => {
+            let next_index =
+                common_key_events::on_low_press_handler(app.discover.current_categories());
+            app.discover.category_index = next_index;
+        }
+        k if k == app.user_config.keys.toggle_playlist_order => app.toggle_discover_order(),
+        // 获取选中分类的歌单
+        Key::Enter => {
+            app.load_discover_playlists();
+            focus_playlists(app);
+        }
+        _ => {}
+    }
+}
+
+pub fn playlist_handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::left_event(k) => app.set_current_route_state(
+            Some(ActiveBlock::DiscoverCategories),
+            Some(ActiveBlock::DiscoverCategories),
+        ),
+        k if common_key_events::down_event(k) => {
+            let next_index = common_key_events::on_down_press_handler(
+                &app.discover.playlists,
+                Some(app.discover.selected_index),
+            );
+            app.discover.selected_index = next_index;
+        }
+        k if common_key_events::up_event(k) => {
+            let next_index = common_key_events::on_up_press_handler(
+                &app.discover.playlists,
+                Some(app.discover.selected_index),
+            );
+            app.discover.selected_index = next_index;
+        }
+        k if common_key_events::high_event(k) => {
+            app.discover.selected_index = common_key_events::on_high_press_handler();
+        }
+        k if common_key_events::middle_event(k) => {
+            let next_index = common_key_events::on_middle_press_handler(&app.discover.playlists);
+            app.discover.selected_index = next_index;
+        }
+        k if common_key_events::low_event(k) => {
+            app.discover.selected_index = app.discover.playlists.len().saturating_sub(1);
+        }
+        k if k == app.user_config.keys.next_page => {
+            let next_index = common_key_events::on_down_or_up_press_handler(
+                &app.discover.playlists,
+                Some(app.discover.selected_index),
+                KeyAction::Down,
+                20,
+            );
+            app.discover.selected_index = next_index;
+        }
+        k if k == app.user_config.keys.previous_page => {
+            let next_index = common_key_events::on_down_or_up_press_handler(
+                &app.discover.playlists,
+                Some(app.discover.selected_index),
+                KeyAction::Up,
+                20,
+            );
+            app.discover.selected_index = next_index;
+        }
+        k if k == app.user_config.keys.jump_to_start => {
+            app.discover.selected_index = 0;
+        }
+        k if k == app.user_config.keys.jump_to_end => {
+            app.discover.selected_index = app.discover.playlists.len().saturating_sub(1);
+        }
+        k if k == app.user_config.keys.toggle_playlist_order => app.toggle_discover_order(),
+        // 收藏或取消收藏歌单
+        Key::Char('s') => {
+            if let Some(playlist) = app.discover.playlists.get(app.discover.selected_index) {
+                let id = playlist.id;
+                app.dispatch(IoEvent::ToggleSubscribePlaylist(id));
+            }
+        }
+        Key::Enter => {
+            if let Some(playlist) = app.discover.playlists.get(app.discover.selected_index) {
+                let id = playlist.id;
+                app.dispatch(IoEvent::GetPlaylistTracks(id));
+            }
+        }
+        _ => {}
+    }
+    app.prefetch_discover_playlists();
+}
+
+fn focus_playlists(app: &mut App) {
+    app.set_current_route_state(
+        Some(ActiveBlock::DiscoverPlaylists),
+        Some(ActiveBlock::DiscoverPlaylists),
+    );
+}
@@ -43,8 +43,15 @@ pub fn handles(key: Key, app: &mut App) {
                 app.track_table.context = Some(TrackTableContext::RecommendedTracks);
                 app.dispatch(IoEvent::GetRecommendTracks);
             } else if app.library.selected_index == 3 {
+                app.open_personal_fm();
+            } else if app.library.selected_index == 4 {
                 app.dispatch(IoEvent::GetArtistSubList);
                 app.push_navigation_stack(RouteId::Artists, ActiveBlock::Artists);
+            } else if app.library.selected_index == 5 {
+                app.dispatch(IoEvent::GetToplists);
+                app.push_navigation_stack(RouteId::Toplists, ActiveBlock::Toplists);
+            } else if app.library.selected_index == 6 {
+                app.open_discover();
             }
         }
         _ => {}
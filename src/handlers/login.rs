@@ -1,9 +1,14 @@
 use crate::app::{ActiveBlock, App};
 use crate::event::Key;
 use crate::handlers::common_key_events;
+use crate::http::write_country_code;
 use crate::model::login::LoginState::NoActive;
-use crate::model::login::{LoginForm, LoginInfo, LoginState};
+use crate::model::login::{
+    CaptchaLoginForm, EmailLoginForm, LoginForm, LoginInfo, LoginMode, LoginState, QrLoginState,
+};
 use crate::IoEvent;
+use anyhow::anyhow;
+use std::time::Instant;
 
 pub fn phone_input_handler(key: Key, app: &mut App) {
     match key {
@@ -20,6 +25,36 @@ pub fn phone_input_handler(key: Key, app: &mut App) {
     }
 }
 
+pub fn email_input_handler(key: Key, app: &mut App) {
+    match key {
+        Key::Esc => {
+            app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::EmailBlock));
+        }
+        Key::Char(c) => {
+            app.login_info.email.push(c);
+        }
+        Key::Backspace => {
+            app.login_info.email.pop();
+        }
+        _ => {}
+    }
+}
+
+pub fn cookie_input_handler(key: Key, app: &mut App) {
+    match key {
+        Key::Esc => {
+            app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::CookieBlock));
+        }
+        Key::Char(c) => {
+            app.login_info.cookie.push(c);
+        }
+        Key::Backspace => {
+            app.login_info.cookie.pop();
+        }
+        _ => {}
+    }
+}
+
 pub fn password_input_handler(key: Key, app: &mut App) {
     match key {
         Key::Esc => {
@@ -34,13 +69,94 @@ pub fn password_input_handler(key: Key, app: &mut App) {
         _ => {}
     }
 }
+
+pub fn captcha_input_handler(key: Key, app: &mut App) {
+    match key {
+        Key::Esc => {
+            app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::CaptchaBlock));
+        }
+        Key::Char(c) => {
+            app.login_info.captcha.push(c);
+        }
+        Key::Backspace => {
+            app.login_info.captcha.pop();
+        }
+        _ => {}
+    }
+}
+
+// 选择国家/地区，输入字符进行搜索
+pub fn country_handler(key: Key, app: &mut App) {
+    match key {
+        Key::Esc => {
+            app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::CountryBlock));
+        }
+        Key::Enter => {
+            let code = app
+                .login_info
+                .filtered_countries()
+                .get(app.login_info.country_selected_index)
+                .map(|c| c.code.clone());
+            if let Some(code) = code {
+                if let Err(e) = write_country_code(&code) {
+                    app.handle_error(e);
+                    return;
+                }
+                app.login_info.country_code = code;
+                app.set_current_route_state(
+                    Some(ActiveBlock::Empty),
+                    Some(ActiveBlock::CountryBlock),
+                );
+            }
+        }
+        Key::Down => {
+            let countries = app.login_info.filtered_countries();
+            let next_index = common_key_events::on_down_press_handler(
+                &countries,
+                Some(app.login_info.country_selected_index),
+            );
+            app.login_info.country_selected_index = next_index;
+        }
+        Key::Up => {
+            let countries = app.login_info.filtered_countries();
+            let next_index = common_key_events::on_up_press_handler(
+                &countries,
+                Some(app.login_info.country_selected_index),
+            );
+            app.login_info.country_selected_index = next_index;
+        }
+        Key::Char(c) => {
+            app.login_info.country_search.push(c);
+            app.login_info.country_selected_index = 0;
+        }
+        Key::Backspace => {
+            app.login_info.country_search.pop();
+            app.login_info.country_selected_index = 0;
+        }
+        _ => {}
+    }
+}
+
+// 打开国家/地区选择框
+pub fn open_country_picker(app: &mut App) {
+    app.login_info.country_search.clear();
+    app.login_info.country_selected_index = 0;
+    app.set_current_route_state(Some(ActiveBlock::CountryBlock), None);
+    if app.login_info.countries.is_empty() {
+        app.dispatch(IoEvent::GetCountries);
+    }
+}
+
 pub fn login_button_handler(key: Key, app: &mut App) {
     let info = app.login_info.clone();
     match key {
         Key::Enter => match info.login_state {
-            LoginState::Confirm => {
-                login(info.phone, info.password, app);
-            }
+            LoginState::Confirm => match info.login_mode {
+                LoginMode::Sms => captcha_login(info.country_code, info.phone, info.captcha, app),
+                LoginMode::Email => email_login(info.email, info.password, app),
+                LoginMode::Cookie => cookie_login(info.cookie, app),
+                _ => login(info.country_code, info.phone, info.password, app),
+            },
             LoginState::Cancel => {
                 app.login_info.cancel_login = true;
             }
@@ -50,7 +166,12 @@ pub fn login_button_handler(key: Key, app: &mut App) {
             app.pop_navigation_stack();
         }
         k if common_key_events::up_event(k) => {
-            app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::PasswordBlock));
+            let hovered = match info.login_mode {
+                LoginMode::Sms => ActiveBlock::CaptchaBlock,
+                LoginMode::Cookie => ActiveBlock::CookieBlock,
+                _ => ActiveBlock::PasswordBlock,
+            };
+            app.set_current_route_state(Some(ActiveBlock::Empty), Some(hovered));
             app.login_info.login_state = NoActive;
         }
         Key::Right | Key::Left => {
@@ -67,6 +188,129 @@ pub fn login_button_handler(key: Key, app: &mut App) {
     }
 }
 
-fn login(phone: String, password: String, app: &mut App) {
-    app.dispatch(IoEvent::Login(LoginForm { phone, password }));
+fn login(country_code: String, phone: String, password: String, app: &mut App) {
+    app.dispatch(IoEvent::Login(LoginForm {
+        country_code,
+        phone,
+        password,
+    }));
+}
+
+fn email_login(email: String, password: String, app: &mut App) {
+    if !is_valid_email(&email) {
+        app.handle_error(anyhow!("邮箱格式不正确"));
+        return;
+    }
+    if password.is_empty() {
+        app.handle_error(anyhow!("请输入密码"));
+        return;
+    }
+    app.dispatch(IoEvent::EmailLogin(EmailLoginForm { email, password }));
+}
+
+// 该登录方式下的第一个输入框
+pub fn first_block(mode: LoginMode) -> ActiveBlock {
+    match mode {
+        LoginMode::Phone | LoginMode::Sms => ActiveBlock::PhoneBlock,
+        LoginMode::Email => ActiveBlock::EmailBlock,
+        LoginMode::QrCode => ActiveBlock::QrCodeBlock,
+        LoginMode::Cookie => ActiveBlock::CookieBlock,
+    }
+}
+
+// 简单校验邮箱格式：name@domain.suffix
+fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((name, domain)) => {
+            !name.is_empty()
+                && !domain.contains('@')
+                && domain
+                    .split_once('.')
+                    .is_some_and(|(host, suffix)| !host.is_empty() && !suffix.is_empty())
+        }
+        None => false,
+    }
+}
+
+fn cookie_login(cookie: String, app: &mut App) {
+    if cookie.trim().is_empty() {
+        app.handle_error(anyhow!("请输入Cookie"));
+        return;
+    }
+    app.dispatch(IoEvent::CookieLogin(cookie));
+}
+
+fn captcha_login(country_code: String, phone: String, captcha: String, app: &mut App) {
+    app.dispatch(IoEvent::CaptchaLogin(CaptchaLoginForm {
+        country_code,
+        phone,
+        captcha,
+    }));
+}
+
+// 发送短信验证码，冷却时间内不重复发送
+pub fn send_captcha(app: &mut App) {
+    if app.login_info.captcha_cooldown_remaining() > 0 {
+        return;
+    }
+    if app.login_info.phone.is_empty() {
+        app.handle_error(anyhow!("请先输入手机号"));
+        return;
+    }
+    app.login_info.captcha_sent_time = Some(Instant::now());
+    app.dispatch(IoEvent::SendCaptcha(
+        app.login_info.country_code.clone(),
+        app.login_info.phone.clone(),
+    ));
+}
+
+// Ctrl-t切换登录方式
+pub fn switch_login_mode(app: &mut App) {
+    app.login_info.login_mode = app.login_info.login_mode.next();
+    app.login_info.login_state = NoActive;
+    let first_block = first_block(app.login_info.login_mode);
+    app.set_current_route_state(Some(first_block), Some(first_block));
+    if app.login_info.login_mode == LoginMode::QrCode {
+        let is_valid = matches!(
+            &app.login_info.qr_login,
+            Some(qr_login) if !matches!(qr_login.state, QrLoginState::Expired | QrLoginState::Failed)
+        );
+        if !is_valid {
+            app.dispatch(IoEvent::GetLoginQrCode);
+        }
+    }
+}
+
+pub fn qr_code_handler(key: Key, app: &mut App) {
+    match key {
+        Key::Esc => {
+            app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::QrCodeBlock));
+        }
+        // 刷新二维码
+        Key::Enter | Key::Char('r') => {
+            app.login_info.qr_login = None;
+            app.dispatch(IoEvent::GetLoginQrCode);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_email;
+
+    #[test]
+    fn test_is_valid_email() {
+        assert!(is_valid_email("user@163.com"));
+        assert!(is_valid_email("first.last@mail.example.com"));
+        // 缺少@
+        assert!(!is_valid_email("user163.com"));
+        assert!(!is_valid_email(""));
+        // 域名为空或不完整
+        assert!(!is_valid_email("user@"));
+        assert!(!is_valid_email("user@.com"));
+        assert!(!is_valid_email("user@163."));
+        assert!(!is_valid_email("@163.com"));
+        assert!(!is_valid_email("user@a@163.com"));
+    }
 }
@@ -1,20 +1,28 @@
 pub use input::handler as input_handler;
+pub use login::captcha_input_handler;
+pub use login::cookie_input_handler;
+pub use login::country_handler;
+pub use login::email_input_handler;
 pub use login::login_button_handler;
 pub use login::password_input_handler;
 pub use login::phone_input_handler;
+pub use login::qr_code_handler;
+pub use login::switch_login_mode;
 
 use crate::app::{ActiveBlock, App, RouteId};
 use crate::event::{IoEvent, Key};
 use crate::handlers::search::SearchResultBlock;
 use crate::model::artist::ArtistBlock;
 use crate::model::enums::ToggleState;
-use crate::model::login::LoginState;
+use crate::model::login::{LoginMode, LoginState};
 
+mod accounts;
 mod album_tracks;
 mod artist_detail;
 mod artists;
 pub(crate) mod common_key_events;
 mod dialog;
+mod discover;
 pub(crate) mod empty;
 pub(crate) mod error_screen;
 pub(crate) mod help_menu;
@@ -24,10 +32,12 @@ pub(crate) mod library;
 mod login;
 pub(crate) mod lyric;
 pub(crate) mod my_playlist;
+mod personal_fm;
 pub(crate) mod playbar;
 pub(crate) mod search;
 mod search_results;
 mod subscribe_playlist;
+mod toplists;
 pub(crate) mod track_table;
 
 pub fn handle_app(key: Key, app: &mut App) {
@@ -75,6 +85,9 @@ pub fn handle_app(key: Key, app: &mut App) {
         _ if key == app.user_config.keys.show_playbar_lyric => {
             app.is_show_playbar_lyric = !app.is_show_playbar_lyric;
         }
+        _ if key == app.user_config.keys.manage_accounts => {
+            app.open_accounts();
+        }
         _ if key == app.user_config.keys.seek_forwards => app.dispatch(IoEvent::SeekForwards),
         _ if key == app.user_config.keys.seek_backwards => app.dispatch(IoEvent::SeekBackForwards),
         _ => handle_block_events(key, app),
@@ -135,6 +148,21 @@ pub fn handle_block_events(key: Key, app: &mut App) {
         ActiveBlock::AlbumTracks => {
             album_tracks::handler(key, app);
         }
+        ActiveBlock::Accounts => {
+            accounts::handler(key, app);
+        }
+        ActiveBlock::PersonalFm => {
+            personal_fm::handler(key, app);
+        }
+        ActiveBlock::Toplists => {
+            toplists::handler(key, app);
+        }
+        ActiveBlock::DiscoverCategories => {
+            discover::category_handler(key, app);
+        }
+        ActiveBlock::DiscoverPlaylists => {
+            discover::playlist_handler(key, app);
+        }
         _ => {}
     }
 }
@@ -182,14 +210,29 @@ pub fn handle_app_login(key: Key, app: &mut App) {
                 match key {
                     Key::Enter => {
                         let current_hovered = app.get_current_route().hovered_block;
-                        app.set_current_route_state(Some(current_hovered), None);
+                        if current_hovered == ActiveBlock::SendCaptchaButton {
+                            login::send_captcha(app);
+                        } else if current_hovered == ActiveBlock::CountryBlock {
+                            login::open_country_picker(app);
+                        } else {
+                            app.set_current_route_state(Some(current_hovered), None);
+                        }
                     }
                     k if common_key_events::down_event2(k) => {
                         match app.get_current_route().hovered_block {
-                            ActiveBlock::PhoneBlock => {
-                                app.set_current_route_state(None, Some(ActiveBlock::PasswordBlock));
+                            ActiveBlock::PhoneBlock
+                            | ActiveBlock::CountryBlock
+                            | ActiveBlock::EmailBlock => {
+                                let next = match app.login_info.login_mode {
+                                    LoginMode::Sms => ActiveBlock::CaptchaBlock,
+                                    _ => ActiveBlock::PasswordBlock,
+                                };
+                                app.set_current_route_state(None, Some(next));
                             }
-                            ActiveBlock::PasswordBlock => {
+                            ActiveBlock::PasswordBlock
+                            | ActiveBlock::CaptchaBlock
+                            | ActiveBlock::SendCaptchaButton
+                            | ActiveBlock::CookieBlock => {
                                 app.set_current_route_state(
                                     Some(ActiveBlock::LoginButton),
                                     Some(ActiveBlock::LoginButton),
@@ -200,10 +243,36 @@ pub fn handle_app_login(key: Key, app: &mut App) {
                         }
                     }
                     k if common_key_events::up_event(k) => {
-                        if app.get_current_route().hovered_block == ActiveBlock::PasswordBlock {
-                            app.set_current_route_state(None, Some(ActiveBlock::PhoneBlock));
+                        if matches!(
+                            app.get_current_route().hovered_block,
+                            ActiveBlock::PasswordBlock
+                                | ActiveBlock::CaptchaBlock
+                                | ActiveBlock::SendCaptchaButton
+                        ) {
+                            let first_block = login::first_block(app.login_info.login_mode);
+                            app.set_current_route_state(None, Some(first_block));
                         }
                     }
+                    k if common_key_events::right_event(k)
+                        && current_route.hovered_block == ActiveBlock::CaptchaBlock =>
+                    {
+                        app.set_current_route_state(None, Some(ActiveBlock::SendCaptchaButton));
+                    }
+                    k if common_key_events::right_event(k)
+                        && current_route.hovered_block == ActiveBlock::CountryBlock =>
+                    {
+                        app.set_current_route_state(None, Some(ActiveBlock::PhoneBlock));
+                    }
+                    k if common_key_events::left_event(k)
+                        && current_route.hovered_block == ActiveBlock::PhoneBlock =>
+                    {
+                        app.set_current_route_state(None, Some(ActiveBlock::CountryBlock));
+                    }
+                    k if common_key_events::left_event(k)
+                        && current_route.hovered_block == ActiveBlock::SendCaptchaButton =>
+                    {
+                        app.set_current_route_state(None, Some(ActiveBlock::CaptchaBlock));
+                    }
                     _ => {}
                 }
             }
@@ -0,0 +1,20 @@
+use crate::app::App;
+use crate::event::Key;
+use crate::handlers::common_key_events;
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        // 不喜欢当前歌曲，移至垃圾桶并跳过
+        k if k == app.user_config.keys.fm_trash => app.trash_fm_track(),
+        // 以FM的当前歌曲开始相似歌曲电台
+        k if k == app.user_config.keys.start_radio => {
+            if let Some(track) = app.personal_fm.current().cloned() {
+                app.start_radio(track);
+            }
+        }
+        // 正在播放其他列表时，切换回私人FM
+        Key::Enter if !app.is_playing_fm() => app.play_personal_fm(),
+        _ => {}
+    }
+}
@@ -1,3 +1,4 @@
+use crate::http::response::ApiResp;
 use crate::model::album::Album;
 use crate::model::artist::Artist;
 use crate::model::playlist::Playlist;
@@ -70,6 +71,10 @@ pub struct ResultResp<T> {
     pub result: Option<T>,
 }
 
+impl<T: serde::de::DeserializeOwned> ApiResp for ResultResp<T> {
+    const ERROR: &'static str = "搜索失败";
+}
+
 pub type SearchTrackResp = ResultResp<SearchResultTrack>;
 pub type SearchArtistResp = ResultResp<SearchResultArtist>;
 pub type SearchPlaylistResp = ResultResp<SearchResultPlaylist>;
@@ -91,6 +91,11 @@ pub fn handler(key: Key, app: &mut App) {
         k if k == app.user_config.keys.add_item_to_queue => {
             add_to_queue(app);
         }
+        k if k == app.user_config.keys.start_radio
+            && app.search_results.selected_block == SearchResultBlock::TrackSearch =>
+        {
+            start_radio(app);
+        }
         Key::Enter => match app.search_results.selected_block {
             SearchResultBlock::Empty => handle_enter_event_on_hovered_block(app),
             SearchResultBlock::PlaylistSearch => {
@@ -114,6 +119,19 @@ fn add_to_queue(app: &mut App) {
     }
 }
 
+fn start_radio(app: &mut App) {
+    let index = app.search_results.selected_tracks_index.unwrap_or(0);
+    let track = app
+        .search_results
+        .tracks
+        .as_ref()
+        .and_then(|tracks| tracks.get(index))
+        .cloned();
+    if let Some(track) = track {
+        app.start_radio(track);
+    }
+}
+
 fn handle_toggle_subscribe_artist_event(app: &mut App) {
     let selected_index = app.search_results.selected_artists_index.unwrap();
     let artists = app.search_results.artists.clone().unwrap();
@@ -362,6 +380,7 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
                         tracks,
                         selected_index: index.unwrap(),
                         context: Some(TrackTableContext::SearchResult),
+                        ..Default::default()
                     };
                     // 将下一曲播放队列置为空
                     app.next_play_tracks = vec![];
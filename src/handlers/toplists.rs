@@ -0,0 +1,67 @@
+use crate::event::Key;
+use crate::handlers::common_key_events;
+use crate::handlers::common_key_events::KeyAction;
+use crate::{App, IoEvent};
+
+pub fn handler(key: Key, app: &mut App) {
+    match key {
+        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(k) => {
+            let next_index = common_key_events::on_down_press_handler(
+                &app.toplists,
+                Some(app.toplists_selected_index),
+            );
+            app.toplists_selected_index = next_index;
+        }
+        k if common_key_events::up_event(k) => {
+            let next_index = common_key_events::on_up_press_handler(
+                &app.toplists,
+                Some(app.toplists_selected_index),
+            );
+            app.toplists_selected_index = next_index;
+        }
+        k if common_key_events::high_event(k) => {
+            let next_index = common_key_events::on_high_press_handler();
+            app.toplists_selected_index = next_index;
+        }
+        k if common_key_events::middle_event(k) => {
+            let next_index = common_key_events::on_middle_press_handler(&app.toplists);
+            app.toplists_selected_index = next_index;
+        }
+        k if common_key_events::low_event(k) => {
+            let next_index = common_key_events::on_low_press_handler(&app.toplists);
+            app.toplists_selected_index = next_index;
+        }
+        k if k == app.user_config.keys.next_page => {
+            let next_index = common_key_events::on_down_or_up_press_handler(
+                &app.toplists,
+                Some(app.toplists_selected_index),
+                KeyAction::Down,
+                20,
+            );
+            app.toplists_selected_index = next_index;
+        }
+        k if k == app.user_config.keys.previous_page => {
+            let next_index = common_key_events::on_down_or_up_press_handler(
+                &app.toplists,
+                Some(app.toplists_selected_index),
+                KeyAction::Up,
+                20,
+            );
+            app.toplists_selected_index = next_index;
+        }
+        k if k == app.user_config.keys.jump_to_start => {
+            app.toplists_selected_index = 0;
+        }
+        k if k == app.user_config.keys.jump_to_end => {
+            app.toplists_selected_index = app.toplists.len().saturating_sub(1);
+        }
+        Key::Enter => {
+            if let Some(toplist) = app.toplists.get(app.toplists_selected_index) {
+                let (id, name) = (toplist.id, toplist.name.clone());
+                app.dispatch(IoEvent::GetToplistTracks(id, name));
+            }
+        }
+        _ => {}
+    }
+}
@@ -16,6 +16,10 @@ pub fn handler(key: Key, app: &mut App) {
             );
             app.track_table.selected_index = next_index;
         }
+        // 在第一首时向上跳转到歌单真正的末尾
+        k if common_key_events::up_event(k) && app.track_table.selected_index == 0 => {
+            jump_to_end(app);
+        }
         k if common_key_events::up_event(k) => {
             let next_index = common_key_events::on_up_press_handler(
                 &app.track_table.tracks,
@@ -56,9 +60,7 @@ pub fn handler(key: Key, app: &mut App) {
         k if k == app.user_config.keys.jump_to_start => {
             app.track_table.selected_index = 0;
         }
-        k if k == app.user_config.keys.jump_to_end => {
-            app.track_table.selected_index = app.track_table.tracks.len() - 1;
-        }
+        k if k == app.user_config.keys.jump_to_end => jump_to_end(app),
         // 跳转到歌手详情页
         k if k == app.user_config.keys.jump_to_artist_detail => {
             let (selected_index, tracks) =
@@ -93,11 +95,45 @@ pub fn handler(key: Key, app: &mut App) {
                 app.dispatch(IoEvent::AddToQueue(track));
             };
         }
+        // 以选中歌曲开始相似歌曲电台
+        k if k == app.user_config.keys.start_radio => {
+            if let Some(track) = app.track_table.tracks.get(app.track_table.selected_index) {
+                let track = track.clone();
+                app.start_radio(track);
+            }
+        }
         Key::Enter => {
             on_enter(app);
         }
         _ => {}
     }
+    prefetch_next_page(app);
+}
+
+// 歌单未加载完时先加载剩余的歌曲，加载后再选中最后一首
+fn jump_to_end(app: &mut App) {
+    if let Some((playlist_id, offset, track_ids)) = app.track_table.remaining_pages() {
+        app.dispatch(IoEvent::GetPlaylistTracksPage(
+            playlist_id,
+            offset,
+            track_ids,
+            true,
+        ));
+    }
+    app.track_table.selected_index = app.track_table.tracks.len().saturating_sub(1);
+}
+
+// 选中位置接近已加载歌曲的末尾时预加载下一页
+fn prefetch_next_page(app: &mut App) {
+    let index = app.track_table.selected_index;
+    if let Some((playlist_id, offset, track_ids)) = app.track_table.next_page(index) {
+        app.dispatch(IoEvent::GetPlaylistTracksPage(
+            playlist_id,
+            offset,
+            track_ids,
+            false,
+        ));
+    }
 }
 
 fn handle_toggle_like_event(app: &mut App) {
@@ -113,18 +149,16 @@ fn on_enter(app: &mut App) {
         context,
         selected_index,
         tracks,
+        pagination,
     } = app.track_table.clone();
     let track = tracks.get(selected_index);
     match &context {
         Some(context) => match context {
-            TrackTableContext::MyPlaylists => {
+            TrackTableContext::MyPlaylists | TrackTableContext::Toplist => {
                 if track.is_some() {
-                    set_track_table(
-                        tracks.clone(),
-                        selected_index,
-                        app,
-                        TrackTableContext::MyPlaylists,
-                    );
+                    set_track_table(tracks.clone(), selected_index, app, context.clone());
+                    // 播放列表随歌单继续分页加载
+                    app.current_play_tracks.pagination = pagination;
                     // 将下一曲播放队列置为空
                     app.next_play_tracks = vec![];
                 }
@@ -173,5 +207,6 @@ fn set_track_table(
         tracks,
         selected_index,
         context: Some(context),
+        ..Default::default()
     };
 }
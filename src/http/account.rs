@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+const ACCOUNTS_FILE_NAME: &str = "accounts.json";
+
+// 已保存的账号
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub user_id: usize,
+    pub nickname: String,
+}
+
+/// 多账号管理，每个账号的cookie保存在账号目录下以用户id命名的文件中，
+/// 切换账号时将对应的cookie复制为当前使用的cookie文件
+pub struct AccountManager {
+    dir: PathBuf,
+    cookie_path: PathBuf,
+}
+
+impl AccountManager {
+    pub fn new(dir: PathBuf, cookie_path: PathBuf) -> Self {
+        Self { dir, cookie_path }
+    }
+
+    pub fn accounts(&self) -> Vec<Account> {
+        fs::read(self.dir.join(ACCOUNTS_FILE_NAME))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// 保存当前登录账号的cookie
+    pub fn save_current(&self, user_id: usize, nickname: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::copy(&self.cookie_path, self.account_cookie_path(user_id))?;
+
+        let mut accounts = self.accounts();
+        let account = Account {
+            user_id,
+            nickname: nickname.to_string(),
+        };
+        match accounts.iter_mut().find(|a| a.user_id == user_id) {
+            Some(a) => *a = account,
+            None => accounts.push(account),
+        }
+        self.write_accounts(&accounts)
+    }
+
+    /// 切换到已保存的账号
+    pub fn switch(&self, user_id: usize) -> Result<()> {
+        let path = self.account_cookie_path(user_id);
+        if !path.exists() {
+            return Err(anyhow!("账号不存在"));
+        }
+        fs::copy(path, &self.cookie_path)?;
+        Ok(())
+    }
+
+    pub fn remove(&self, user_id: usize) -> Result<()> {
+        let path = self.account_cookie_path(user_id);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        let accounts: Vec<Account> = self
+            .accounts()
+            .into_iter()
+            .filter(|a| a.user_id != user_id)
+            .collect();
+        self.write_accounts(&accounts)
+    }
+
+    fn account_cookie_path(&self, user_id: usize) -> PathBuf {
+        self.dir.join(user_id.to_string())
+    }
+
+    fn write_accounts(&self, accounts: &[Account]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.dir.join(ACCOUNTS_FILE_NAME),
+            serde_json::to_vec(accounts)?,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_account_manager() {
+        let dir = tempfile::tempdir().unwrap();
+        let cookie_path = dir.path().join("cookie");
+        let manager = AccountManager::new(dir.path().join("accounts"), cookie_path.clone());
+        assert!(manager.accounts().is_empty());
+
+        fs::write(&cookie_path, "MUSIC_U=a").unwrap();
+        manager.save_current(1, "a").unwrap();
+        fs::write(&cookie_path, "MUSIC_U=b").unwrap();
+        manager.save_current(2, "b").unwrap();
+        assert_eq!(manager.accounts().len(), 2);
+
+        manager.switch(1).unwrap();
+        assert_eq!(fs::read_to_string(&cookie_path).unwrap(), "MUSIC_U=a");
+
+        manager.remove(1).unwrap();
+        assert_eq!(
+            manager.accounts(),
+            vec![Account {
+                user_id: 2,
+                nickname: "b".to_string()
+            }]
+        );
+        assert!(manager.switch(1).is_err());
+    }
+}
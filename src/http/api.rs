@@ -3,21 +3,56 @@ use openssl::hash::{hash, MessageDigest};
 use rand::RngCore;
 use serde_json::json;
 use serde_json::Value;
+use std::time::Duration;
 
 use crate::handlers::search::SearchType;
-use crate::http::client::ApiClient;
+use crate::http::client::{parse_cookies, ApiClient};
 use crate::http::crypto::Crypto::Eapi;
 use crate::http::request::{ApiRequestBuilder, UA};
 use crate::http::response::ApiResponse;
 use crate::http::route::API_ROUTE;
 use crate::model::playlist::PlaylistDetailResp;
 
+// 持久化缓存的有效期
+const LYRIC_CACHE_EXP: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const ALBUM_CACHE_EXP: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const ARTIST_CACHE_EXP: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[derive(Default)]
 pub struct CloudMusicApi {
     client: ApiClient,
 }
 
 impl CloudMusicApi {
+    /// 使用导入的cookie，校验通过后调用save_cookies写入本地
+    pub fn with_cookies(cookies: &str) -> Result<Self> {
+        let cookies = parse_cookies(cookies)?;
+        Ok(CloudMusicApi {
+            client: ApiClient::with_cookies(&cookies)?,
+        })
+    }
+
+    pub fn save_cookies(&self) -> Result<()> {
+        self.client.save_cookies()
+    }
+
+    /// 使用给定的传输层，cookie和缓存写入临时目录，用于测试
+    #[cfg(test)]
+    pub(crate) fn with_transport(
+        transport: std::sync::Arc<dyn crate::http::transport::Transport>,
+    ) -> crate::http::transport::WithTempDir<Self> {
+        crate::http::transport::WithTempDir::new(|dir| CloudMusicApi {
+            client: crate::http::client::ApiClientBuilder::new(
+                dir.join("cookies").to_str().unwrap(),
+                false,
+            )
+            .cache_dir(dir.join("http_cache").to_str().unwrap())
+            .transport(transport)
+            .build()
+            .unwrap(),
+        })
+    }
+
     // pub fn new(
     //     enable_cache: bool,
     //     cache_exp: Duration,
@@ -44,12 +79,17 @@ impl CloudMusicApi {
     /// 可选参数 :
     /// countrycode: 国家码，用于国外手机号登录，例如美国传入：1
     /// md5_password: md5加密后的密码,传入后 password 将失效
-    pub async fn login_phone(&self, phone: &str, password: &str) -> Result<ApiResponse> {
+    pub async fn login_phone(
+        &self,
+        country_code: &str,
+        phone: &str,
+        password: &str,
+    ) -> Result<ApiResponse> {
         let password = md5_hex(password.as_bytes());
         let r = ApiRequestBuilder::post(API_ROUTE["login_cellphone"])
             .add_cookie("os", "pc")
             .set_data(json!({
-                "countrycode":   "86",
+                "countrycode":   country_code,
                 "rememberLogin": true,
                 "phone": phone,
                 "password": password,
@@ -58,6 +98,108 @@ impl CloudMusicApi {
         self.client.login(true).request(r).await
     }
 
+    /// 必选参数 :
+    /// email: 163 网易邮箱
+    /// password: 密码
+    pub async fn login_email(&self, email: &str, password: &str) -> Result<ApiResponse> {
+        let password = md5_hex(password.as_bytes());
+        let r = ApiRequestBuilder::post(API_ROUTE["login"])
+            .add_cookie("os", "pc")
+            .set_data(json!({
+                "username": email,
+                "password": password,
+                "rememberLogin": true,
+            }))
+            .build();
+        self.client.login(true).request(r).await
+    }
+
+    /// 说明 : 调用此接口 , 传入手机号码 , 可发送验证码
+    ///
+    /// 必选参数 : phone: 手机号码
+    ///
+    /// 可选参数 : country_code: 国家区号,默认 86 即中国
+    pub async fn captcha_sent(&self, country_code: &str, phone: &str) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["captcha_sent"])
+            .set_data(json!({ "ctcode": country_code, "cellphone": phone }))
+            .set_idempotent(false)
+            .build();
+
+        self.client.request(r).await
+    }
+
+    /// 说明 : 调用此接口 , 传入手机号码和验证码 , 可校验验证码是否正确
+    ///
+    /// 必选参数 : phone: 手机号码, captcha: 验证码
+    pub async fn captcha_verify(
+        &self,
+        country_code: &str,
+        phone: &str,
+        captcha: &str,
+    ) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["captcha_verify"])
+            .set_data(json!({ "ctcode": country_code, "cellphone": phone, "captcha": captcha }))
+            .build();
+
+        self.client.request(r).await
+    }
+
+    /// 说明 : 使用手机号码和短信验证码登录
+    ///
+    /// 必选参数 : phone: 手机号码, captcha: 验证码
+    pub async fn login_phone_captcha(
+        &self,
+        country_code: &str,
+        phone: &str,
+        captcha: &str,
+    ) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["login_cellphone"])
+            .add_cookie("os", "pc")
+            .set_data(json!({
+                "countrycode":   country_code,
+                "rememberLogin": true,
+                "phone": phone,
+                "captcha": captcha,
+            }))
+            .build();
+        self.client.login(true).request(r).await
+    }
+
+    /// 说明 : 调用此接口,可获取国家编码列表
+    pub async fn countries_code_list(&self) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["countries_code_list"])
+            .set_crypto(Eapi)
+            .set_api_url("/api/lbs/countries/v1")
+            .build();
+
+        self.client.request(r).await
+    }
+
+    /// 说明 : 调用此接口 , 可生成二维码登录所需的 key
+    pub async fn login_qr_key(&self) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["login_qr_key"])
+            .set_data(json!({ "type": 1 }))
+            .build();
+
+        self.client.request(r).await
+    }
+
+    /// 说明 : 传入 key , 生成二维码的内容
+    pub fn login_qr_create(&self, key: &str) -> String {
+        replace_all_route_params(API_ROUTE["login_qr_create"], key)
+    }
+
+    /// 说明 : 轮询此接口可获取二维码扫码状态,800 为二维码过期,801 为等待扫码,802 为待确认,803 为授权登录成功(803 状态码下会返回 cookies)
+    ///
+    /// 必选参数 : key : 由 login_qr_key 生成
+    pub async fn login_qr_check(&self, key: &str) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["login_qr_check"])
+            .set_data(json!({ "key": key, "type": 1 }))
+            .build();
+
+        self.client.login(true).request(r).await
+    }
+
     /// 说明 : 调用此接口 , 可刷新登录状态
     #[allow(unused)]
     pub async fn login_refresh(&self) -> Result<ApiResponse> {
@@ -75,9 +217,10 @@ impl CloudMusicApi {
     }
 
     /// 说明 : 调用此接口 , 可退出登录
-    #[allow(unused)]
     pub async fn logout(&self) -> Result<ApiResponse> {
-        let r = ApiRequestBuilder::post(API_ROUTE["logout"]).build();
+        let r = ApiRequestBuilder::post(API_ROUTE["logout"])
+            .set_idempotent(false)
+            .build();
 
         self.client.request(r).await
     }
@@ -240,7 +383,10 @@ impl CloudMusicApi {
             .set_data(json!({ "id": id, "lv": -1, "tv": -1, "kv": -1 }))
             .build();
 
-        self.client.cache(true).request(r).await
+        self.client
+            .persistent_cache(LYRIC_CACHE_EXP)
+            .request(r)
+            .await
     }
 
     // 记录播放次数
@@ -266,6 +412,7 @@ impl CloudMusicApi {
         let r = ApiRequestBuilder::post(API_ROUTE["weblog"])
             .set_data(json!({"logs": data.to_string()}))
             .set_ua(UA::Android)
+            .set_idempotent(false)
             .build();
 
         self.client.request(r).await
@@ -302,6 +449,57 @@ impl CloudMusicApi {
         self.client.request(r).await
     }
 
+    /// 说明 : 私人 FM( 需要登录 ), 每次调用返回几首推荐歌曲
+    pub async fn personal_fm(&self) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["personal_fm"]).build();
+
+        // 每次返回的歌曲不同，不能缓存
+        self.client.cache(false).request(r).await
+    }
+
+    /// 说明 : 心动模式/智能播放, 返回喜欢的歌曲和推荐的相似歌曲
+    ///
+    /// required
+    /// 必选参数 : id : 歌曲 id, pid : 歌单 id
+    ///
+    /// optional
+    /// 可选参数 : sid : 要开始播放的歌曲 id
+    pub async fn playmode_intelligence_list(
+        &self,
+        id: usize,
+        pid: usize,
+        sid: usize,
+    ) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["playmode_intelligence_list"])
+            .set_data(json!({
+                "songId": id,
+                "type": "fromPlayOne",
+                "playlistId": pid,
+                "startMusicId": sid,
+                "count": 1
+            }))
+            .build();
+
+        // 每次推荐的歌曲不同，不能缓存
+        self.client.cache(false).request(r).await
+    }
+
+    /// 说明 : 把私人 FM 中的歌曲移至垃圾桶, 之后不再推荐
+    ///
+    /// required
+    /// 必选参数 : id: 歌曲 id
+    pub async fn fm_trash(&self, track_id: usize) -> Result<ApiResponse> {
+        let u = API_ROUTE["fm_trash"]
+            .replacen("%v", &track_id.to_string(), 1)
+            .replacen("%v", "25", 1);
+        let r = ApiRequestBuilder::post(&u)
+            .set_data(json!({ "songId": track_id }))
+            .set_idempotent(false)
+            .build();
+
+        self.client.cache(false).request(r).await
+    }
+
     /// 说明 : 调用此接口 , 传入搜索关键词可以搜索该音乐 / 专辑 / 歌手 / 歌单 / 用户 , 关键词可以多个 , 以空格隔开 ,
     /// 如 " 周杰伦 搁浅 "( 不需要登录 ), 搜索获取的 mp3url 不能直接用 , 可通过 /song/url 接口传入歌曲 id 获取具体的播放链接
     ///
@@ -369,7 +567,10 @@ impl CloudMusicApi {
             .set_data(data)
             .build();
 
-        self.client.request(r).await
+        self.client
+            .persistent_cache(ARTIST_CACHE_EXP)
+            .request(r)
+            .await
     }
 
     // 获取歌手专辑
@@ -381,7 +582,10 @@ impl CloudMusicApi {
             .insert("total", Value::Bool(true))
             .build();
 
-        self.client.request(r).await
+        self.client
+            .persistent_cache(ARTIST_CACHE_EXP)
+            .request(r)
+            .await
     }
 
     // 获取相似歌手
@@ -390,6 +594,83 @@ impl CloudMusicApi {
             .set_data(json!({ "artistid": artist_id }))
             .build();
 
+        self.client
+            .persistent_cache(ARTIST_CACHE_EXP)
+            .request(r)
+            .await
+    }
+
+    // 获取歌单分类
+    pub async fn playlist_catlist(&self) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["playlist_catlist"])
+            .set_data(json!({}))
+            .build();
+
+        self.client.request(r).await
+    }
+
+    // 获取精品歌单标签
+    pub async fn playlist_highquality_tags(&self) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["playlist_highquality_tags"])
+            .set_data(json!({}))
+            .build();
+
+        self.client.request(r).await
+    }
+
+    /// 说明 : 按分类获取歌单
+    ///
+    /// 必选参数 : cat: 分类，如"华语"，"全部"为不限分类
+    ///
+    /// order: 排序，"hot"/最热，"new"/最新
+    pub async fn top_playlist(
+        &self,
+        cat: &str,
+        order: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["top_playlist"])
+            .set_data(limit_offset(limit, offset))
+            .merge(json!({ "cat": cat, "order": order, "total": true }))
+            .build();
+
+        self.client.request(r).await
+    }
+
+    /// 说明 : 按分类获取精品歌单
+    ///
+    /// 必选参数 : cat: 分类，需为精品歌单标签中的分类
+    ///
+    /// before: 上一页最后一个歌单的更新时间，第一页为0
+    pub async fn top_playlist_highquality(
+        &self,
+        cat: &str,
+        limit: usize,
+        before: u64,
+    ) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["top_playlist_highquality"])
+            .set_data(json!({ "cat": cat, "limit": limit, "lasttime": before, "total": true }))
+            .build();
+
+        self.client.request(r).await
+    }
+
+    // 获取所有榜单，官方榜带有前三名歌曲
+    pub async fn toplist_detail(&self) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["toplist_detail"])
+            .set_data(json!({}))
+            .build();
+
+        self.client.request(r).await
+    }
+
+    // 获取相似歌曲
+    pub async fn simi_songs(&self, track_id: usize) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["simi_song"])
+            .set_data(json!({ "songid": track_id, "limit": 50, "offset": 0 }))
+            .build();
+
         self.client.request(r).await
     }
 
@@ -398,7 +679,10 @@ impl CloudMusicApi {
         let u = replace_all_route_params(API_ROUTE["album"], album_id.to_string().as_str());
         let r = ApiRequestBuilder::post(&u).build();
 
-        self.client.request(r).await
+        self.client
+            .persistent_cache(ALBUM_CACHE_EXP)
+            .request(r)
+            .await
     }
 
     // 收藏or取消收藏歌手
@@ -410,6 +694,40 @@ impl CloudMusicApi {
             .build();
         self.client.request(r).await
     }
+
+    /// 说明 : 批量请求接口 , 一次返回多个接口的结果 , 响应中以接口路径为键
+    ///
+    /// required
+    /// 必选参数 : requests: 接口路径和参数 , 如 ("/api/v1/user/detail/32953014", json!({}))
+    #[allow(unused)]
+    pub async fn batch(&self, requests: &[(&str, Value)]) -> Result<ApiResponse> {
+        // e_r 表示响应体需要加密
+        let mut data = json!({ "e_r": true });
+        for (path, params) in requests {
+            data[*path] = Value::String(params.to_string());
+        }
+        let r = ApiRequestBuilder::post(API_ROUTE["batch"])
+            .set_crypto(Eapi)
+            .set_api_url("/api/batch")
+            .set_data(data)
+            .build();
+        self.client.cache(false).request(r).await
+    }
+
+    /// 说明 : 刚注册的账号(需登录)调用此接口 , 可初始化昵称
+    ///
+    /// required
+    /// 必选参数 : nickname : 昵称
+    #[allow(unused)]
+    pub async fn activate_init_profile(&self, nickname: &str) -> Result<ApiResponse> {
+        let r = ApiRequestBuilder::post(API_ROUTE["activate_init_profile"])
+            .set_crypto(Eapi)
+            .set_api_url("/api/activate/initProfile")
+            .set_data(json!({ "nickname": nickname, "e_r": true }))
+            .set_idempotent(false)
+            .build();
+        self.client.cache(false).request(r).await
+    }
 }
 fn replace_all_route_params(u: &str, rep: &str) -> String {
     let re = regex::Regex::new(r"\$\{.*\}").unwrap();
@@ -429,6 +747,193 @@ fn limit_offset(limit: usize, offset: usize) -> Value {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+
+    use crate::handlers::search::{SearchAlbumResp, SearchType};
+    use crate::http::api::CloudMusicApi;
+    use crate::http::key::EAPI_KEY;
+    use crate::http::route::API_ROUTE;
+    use crate::http::transport::{track_json, FixtureTransport, WithTempDir};
+    use crate::model::playlist::{PlaylistDetailResp, PlaylistTracksResp};
+    use crate::model::table::RecentlyPlayedResp;
+    use crate::model::track::LyricResp;
+    use crate::model::user::LikeTrackIdListResp;
+
+    // 模拟e_r为true时eapi接口返回的加密响应体
+    fn encrypt_response(body: &Value) -> Vec<u8> {
+        openssl::symm::encrypt(
+            openssl::symm::Cipher::aes_128_ecb(),
+            EAPI_KEY.as_bytes(),
+            None,
+            body.to_string().as_bytes(),
+        )
+        .unwrap()
+    }
+
+    fn api(transport: FixtureTransport) -> WithTempDir<CloudMusicApi> {
+        CloudMusicApi::with_transport(Arc::new(transport))
+    }
+
+    #[tokio::test]
+    async fn test_recent_song() {
+        let api = api(FixtureTransport::new().route(
+            API_ROUTE["recent_song_list"],
+            json!({
+                "code": 200,
+                "data": {
+                    "total": 1,
+                    "list": [{
+                        "resourceId": "527629786",
+                        "playTime": 1650000000000u64,
+                        "resourceType": "SONG",
+                        "data": track_json(527629786, "再来一杯")
+                    }]
+                }
+            }),
+        ));
+        let resp = api.recent_song_list(10).await.unwrap();
+        let resp = serde_json::from_slice::<RecentlyPlayedResp>(resp.data()).unwrap();
+        assert_eq!(resp.data.list.len(), 1);
+        assert_eq!(resp.data.list[0].data.name, "再来一杯");
+    }
+
+    #[tokio::test]
+    async fn test_like_list() {
+        let api = api(FixtureTransport::new().route_with_params(
+            API_ROUTE["likelist"],
+            json!({ "uid": 786011886 }),
+            json!({ "code": 200, "ids": [527629786, 1479526505] }),
+        ));
+        let resp = api.like_list(786011886).await.unwrap();
+        let resp = serde_json::from_slice::<LikeTrackIdListResp>(resp.data()).unwrap();
+        assert!(resp.ids.contains(&527629786));
+        assert_eq!(resp.ids.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lyric() {
+        let api = api(FixtureTransport::new().route_with_params(
+            API_ROUTE["lyric"],
+            json!({ "id": 527629786 }),
+            json!({
+                "code": 200,
+                "lrc": { "lyric": "[00:23.14]曾经我觉得我被世间遗忘\n" },
+                "tlyric": { "lyric": "" }
+            }),
+        ));
+        let resp = api.lyric(527629786).await.unwrap();
+        let resp = serde_json::from_slice::<LyricResp>(resp.data()).unwrap();
+        assert_eq!(resp.code, 200);
+        assert!(resp.lrc.lyric.contains("世间遗忘"));
+    }
+
+    #[tokio::test]
+    async fn test_pl() {
+        let api = api(FixtureTransport::new().route_with_params(
+            API_ROUTE["playlist_detail"],
+            json!({ "id": 498339500 }),
+            json!({
+                "code": 200,
+                "playlist": {
+                    "id": 498339500,
+                    "name": "我喜欢的音乐",
+                    "description": null,
+                    "trackIds": [{ "id": 1 }, { "id": 2 }],
+                    "userId": 354192143
+                }
+            }),
+        ));
+        let resp = api.playlist_detail(498339500, None).await.unwrap();
+        let resp = serde_json::from_slice::<PlaylistDetailResp>(resp.data()).unwrap();
+        let playlist = resp.playlist.unwrap();
+        assert_eq!(playlist.name, "我喜欢的音乐");
+        assert_eq!(playlist.track_ids.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_playlist_tracks() {
+        let api = api(FixtureTransport::new()
+            .route(
+                API_ROUTE["playlist_detail"],
+                json!({
+                    "code": 200,
+                    "playlist": {
+                        "id": 498339500,
+                        "name": "我喜欢的音乐",
+                        "trackIds": [{ "id": 1 }, { "id": 2 }, { "id": 3 }],
+                        "userId": 354192143
+                    }
+                }),
+            )
+            .route(
+                API_ROUTE["song_detail"],
+                json!({ "code": 200, "songs": [track_json(2, "犯贱"), track_json(3, "再来一杯")] }),
+            ));
+        let resp = api.playlist_tracks(498339500, 1, 10).await.unwrap();
+        let resp = serde_json::from_slice::<PlaylistTracksResp>(resp.data()).unwrap();
+        assert_eq!(resp.tracks.len(), 2);
+        assert_eq!(resp.tracks[0].id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_cloud_search() {
+        let api = api(FixtureTransport::new().route_with_params(
+            API_ROUTE["cloudsearch"],
+            json!({ "s": "不如吃茶去", "type": 10 }),
+            json!({
+                "code": 200,
+                "result": {
+                    "albums": [{ "id": 1, "name": "不如吃茶去", "artist": { "id": 2, "name": "张信哲" } }],
+                    "albumCount": 1
+                }
+            }),
+        ));
+        let resp = api
+            .cloud_search("不如吃茶去", SearchType::Album, None)
+            .await
+            .unwrap();
+        let search_resp = serde_json::from_slice::<SearchAlbumResp>(resp.data()).unwrap();
+        assert_eq!(search_resp.code, 200);
+        assert!(search_resp.result.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_batch() {
+        let body = json!({
+            "code": 200,
+            "/api/v1/user/detail/32953014": { "code": 200, "profile": { "userId": 32953014 } }
+        });
+        let api =
+            api(FixtureTransport::new().route_raw(API_ROUTE["batch"], encrypt_response(&body)));
+        let resp = api
+            .batch(&[("/api/v1/user/detail/32953014", json!({}))])
+            .await
+            .unwrap();
+        let resp = serde_json::from_slice::<Value>(resp.data()).unwrap();
+        assert_eq!(resp, body);
+    }
+
+    #[tokio::test]
+    async fn test_activate_init_profile() {
+        let api = api(FixtureTransport::new().route_raw(
+            API_ROUTE["activate_init_profile"],
+            encrypt_response(&json!({ "code": 200 })),
+        ));
+        let resp = api.activate_init_profile("mota").await.unwrap();
+        assert_eq!(resp.deserialize_to_implict().code, 200);
+    }
+
+    #[tokio::test]
+    async fn test_missing_fixture() {
+        let api = api(FixtureTransport::new());
+        assert!(api.album(32311).await.is_err());
+    }
+}
+
+#[cfg(all(test, feature = "live-tests"))]
+mod live_tests {
     use crate::handlers::search::{SearchAlbumResp, SearchType};
     use crate::http::api::CloudMusicApi;
     use crate::model::playlist::PlaylistDetailResp;
@@ -439,7 +944,7 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn test_login_phone() {
         let api = CloudMusicApi::default();
-        let resp = api.login_phone("xxx", "xxx").await;
+        let resp = api.login_phone("86", "xxx", "xxx").await;
         println!("{:?}", resp);
         assert!(resp.is_ok());
 
@@ -0,0 +1,400 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use openssl::hash::{hash, MessageDigest};
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::http::crypto::eapi_decrypt_response;
+use crate::http::route::API_ROUTE;
+use crate::http::transport::{Transport, TransportRequest};
+
+static CASSETTE_MODE: OnceLock<CassetteMode> = OnceLock::new();
+
+// 录制时替换掉的字段，字段名包含其中之一即替换，避免录制文件泄露账号信息
+const REDACTED_FIELDS: [&str; 6] = ["password", "captcha", "phone", "email", "token", "cookie"];
+
+/// 录制或回放接口请求，录制的文件称为cassette，每个请求一个json文件
+#[derive(Debug, Clone, PartialEq)]
+pub enum CassetteMode {
+    // 正常访问网络
+    Off,
+    // 访问网络，并把请求和响应写入目录
+    Record(PathBuf),
+    // 不访问网络，从目录中读取之前录制的响应，只使用参数完全相同的录制
+    Replay(PathBuf),
+    // 同Replay，没有参数相同的录制时使用同一接口的任意录制
+    ReplayFallback(PathBuf),
+}
+
+impl CassetteMode {
+    /// 命令行参数优先，其次读取环境变量RS_MUSIC_TUI_RECORD、RS_MUSIC_TUI_REPLAY和RS_MUSIC_TUI_REPLAY_FALLBACK
+    pub fn from_args(record: Option<&str>, replay: Option<&str>, replay_fallback: bool) -> Self {
+        let record = record
+            .map(String::from)
+            .or_else(|| env::var("RS_MUSIC_TUI_RECORD").ok());
+        let replay = replay
+            .map(String::from)
+            .or_else(|| env::var("RS_MUSIC_TUI_REPLAY").ok());
+        let replay_fallback = replay_fallback
+            || env::var("RS_MUSIC_TUI_REPLAY_FALLBACK").is_ok_and(|v| !v.is_empty());
+        match (record, replay) {
+            (Some(dir), _) if !dir.is_empty() => CassetteMode::Record(PathBuf::from(dir)),
+            (_, Some(dir)) if !dir.is_empty() && replay_fallback => {
+                CassetteMode::ReplayFallback(PathBuf::from(dir))
+            }
+            (_, Some(dir)) if !dir.is_empty() => CassetteMode::Replay(PathBuf::from(dir)),
+            _ => CassetteMode::Off,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Cassette {
+    // 接口名，即API_ROUTE中的键
+    route: String,
+    url: String,
+    params: Value,
+    status: u16,
+    body: Value,
+}
+
+/// 设置录制/回放模式，需在创建网络客户端前调用
+pub fn init(mode: CassetteMode) {
+    CASSETTE_MODE.set(mode).ok();
+}
+
+/// 按当前的录制/回放模式包装传输层
+pub fn wrap(transport: Arc<dyn Transport>) -> Arc<dyn Transport> {
+    match CASSETTE_MODE.get() {
+        Some(CassetteMode::Record(dir)) => Arc::new(RecordingTransport {
+            inner: transport,
+            dir: dir.clone(),
+        }),
+        Some(CassetteMode::Replay(dir)) => Arc::new(ReplayTransport {
+            dir: dir.clone(),
+            fallback: false,
+        }),
+        Some(CassetteMode::ReplayFallback(dir)) => Arc::new(ReplayTransport {
+            dir: dir.clone(),
+            fallback: true,
+        }),
+        _ => transport,
+    }
+}
+
+/// 转发请求并录制响应
+#[derive(Debug)]
+pub struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    dir: PathBuf,
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    async fn execute(&self, req: TransportRequest) -> Result<Response> {
+        let route = req.route.clone();
+        let params = req.params.clone();
+        let resp = self.inner.execute(req).await?;
+
+        // 读取响应体后需重新构造响应，cookie等响应头原样保留，但不写入文件
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+        // 加密的eapi响应录制解密后的内容，回放时不需要再解密
+        let plain = eapi_decrypt_response(&body);
+        let plain = plain.as_deref().unwrap_or(&body);
+        // 文件名按原始参数计算，回放时才能找到
+        let name = route_name(&route);
+        let path = self.dir.join(file_name(&name, &params));
+        let cassette = Cassette {
+            route: name,
+            url: route,
+            params: redact(params),
+            status: status.as_u16(),
+            body: redact(
+                serde_json::from_slice(plain)
+                    .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(plain).to_string())),
+            ),
+        };
+        // 请求已经完成，录制失败不影响返回结果
+        if let Err(e) = self.write(&path, &cassette) {
+            log::warn!("录制请求失败：{} {}", path.display(), e);
+        }
+
+        let mut builder = http::Response::builder().status(status);
+        for (name, value) in headers.iter() {
+            builder = builder.header(name, value);
+        }
+        Ok(Response::from(builder.body(body)?))
+    }
+}
+
+impl RecordingTransport {
+    fn write(&self, path: &Path, cassette: &Cassette) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, serde_json::to_vec_pretty(cassette)?)?;
+        Ok(())
+    }
+}
+
+// 替换账号、密码、验证码、token等字段的值
+fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let key = k.to_lowercase();
+                    if REDACTED_FIELDS.iter().any(|f| key.contains(f)) {
+                        (k, Value::String("<redacted>".to_owned()))
+                    } else {
+                        (k, redact(v))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(redact).collect()),
+        value => value,
+    }
+}
+
+/// 从录制的文件返回响应，不访问网络
+#[derive(Debug)]
+pub struct ReplayTransport {
+    dir: PathBuf,
+    // 没有参数相同的录制时是否使用同一接口的任意录制
+    fallback: bool,
+}
+
+impl ReplayTransport {
+    // 优先使用参数完全相同的录制，允许回退时其次使用同一接口的任意录制
+    fn find(&self, route: &str, params: &Value) -> Option<Cassette> {
+        let exact = self.dir.join(file_name(route, params));
+        let path = if exact.exists() {
+            exact
+        } else if !self.fallback {
+            return None;
+        } else {
+            let prefix = format!("{}-", route);
+            let mut paths = fs::read_dir(&self.dir)
+                .ok()?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(&prefix))
+                })
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths.into_iter().next()?
+        };
+        serde_json::from_slice(&fs::read(path).ok()?).ok()
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn execute(&self, req: TransportRequest) -> Result<Response> {
+        let route = route_name(&req.route);
+        let cassette = self
+            .find(&route, &req.params)
+            .ok_or_else(|| anyhow!("没有录制的响应：{} {}", route, req.params))?;
+        let body = match cassette.body {
+            Value::String(s) => s,
+            body => body.to_string(),
+        };
+        let resp = http::Response::builder()
+            .status(cassette.status)
+            .body(body)?;
+        Ok(Response::from(resp))
+    }
+}
+
+// 接口地址对应的接口名，地址中带有参数的接口使用地址路径
+fn route_name(url: &str) -> String {
+    if let Some((name, _)) = API_ROUTE.entries().find(|(_, u)| **u == url) {
+        return name.to_string();
+    }
+    let path = url.split("://").last().unwrap_or(url);
+    let path = path.split_once('/').map(|(_, p)| p).unwrap_or(path);
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// 同一接口不同参数的请求分别录制
+fn file_name(route: &str, params: &Value) -> String {
+    let digest = hash(MessageDigest::md5(), params.to_string().as_bytes()).unwrap();
+    format!("{}-{}.json", route, &hex::encode(digest)[..12])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use super::{route_name, CassetteMode, RecordingTransport, ReplayTransport};
+    use crate::http::route::API_ROUTE;
+    use crate::http::transport::{FixtureTransport, Transport, TransportRequest};
+
+    fn request(route: &str, params: serde_json::Value) -> TransportRequest {
+        TransportRequest {
+            route: route.to_owned(),
+            params,
+            request: reqwest::Request::new(
+                reqwest::Method::POST,
+                "https://music.163.com".parse().unwrap(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_route_name() {
+        assert_eq!(route_name(API_ROUTE["lyric"]), "lyric");
+        assert_eq!(
+            route_name("https://music.163.com/weapi/v1/album/32311"),
+            "weapi_v1_album_32311"
+        );
+    }
+
+    async fn body(resp: reqwest::Response) -> serde_json::Value {
+        serde_json::from_slice(&resp.bytes().await.unwrap()).unwrap()
+    }
+
+    // 录制指定id的歌词请求
+    async fn record_lyrics(ids: &[usize]) -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = ids.iter().fold(FixtureTransport::new(), |fixture, id| {
+            fixture.route_with_params(
+                API_ROUTE["lyric"],
+                json!({ "id": id }),
+                json!({ "code": 200, "id": id }),
+            )
+        });
+        let recorder = RecordingTransport {
+            inner: Arc::new(fixture),
+            dir: dir.path().to_path_buf(),
+        };
+        for id in ids {
+            let resp = recorder
+                .execute(request(API_ROUTE["lyric"], json!({ "id": id })))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), 200);
+        }
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = record_lyrics(&[1, 2]).await;
+
+        let replay = ReplayTransport {
+            dir: dir.path().to_path_buf(),
+            fallback: false,
+        };
+        for id in [1, 2] {
+            let resp = replay
+                .execute(request(API_ROUTE["lyric"], json!({ "id": id })))
+                .await
+                .unwrap();
+            assert_eq!(body(resp).await, json!({ "code": 200, "id": id }));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_miss() {
+        let dir = record_lyrics(&[1]).await;
+        let replay = ReplayTransport {
+            dir: dir.path().to_path_buf(),
+            fallback: false,
+        };
+        // 参数不同时不使用其他录制
+        assert!(replay
+            .execute(request(API_ROUTE["lyric"], json!({ "id": 3 })))
+            .await
+            .is_err());
+        assert!(replay
+            .execute(request(API_ROUTE["album"], json!({})))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_fallback() {
+        let dir = record_lyrics(&[1]).await;
+        let replay = ReplayTransport {
+            dir: dir.path().to_path_buf(),
+            fallback: true,
+        };
+        let resp = replay
+            .execute(request(API_ROUTE["lyric"], json!({ "id": 3 })))
+            .await
+            .unwrap();
+        assert_eq!(body(resp).await, json!({ "code": 200, "id": 1 }));
+        // 其他接口没有录制时仍然失败
+        assert!(replay
+            .execute(request(API_ROUTE["album"], json!({})))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_redact_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = RecordingTransport {
+            inner: Arc::new(FixtureTransport::new().route(
+                API_ROUTE["login_cellphone"],
+                json!({ "code": 200, "token": "secret", "profile": { "nickname": "Mr.mo" } }),
+            )),
+            dir: dir.path().to_path_buf(),
+        };
+        let params = json!({ "phone": "13800000000", "password": "md5", "countrycode": "86" });
+        recorder
+            .execute(request(API_ROUTE["login_cellphone"], params.clone()))
+            .await
+            .unwrap();
+
+        let entry = std::fs::read_dir(dir.path()).unwrap().next().unwrap();
+        let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        assert!(!content.contains("13800000000"));
+        assert!(!content.contains("secret"));
+        let cassette: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(cassette["params"]["countrycode"], "86");
+        assert_eq!(cassette["body"]["profile"]["nickname"], "Mr.mo");
+
+        // 仍能按原始参数回放
+        let replay = ReplayTransport {
+            dir: dir.path().to_path_buf(),
+            fallback: false,
+        };
+        assert!(replay
+            .execute(request(API_ROUTE["login_cellphone"], params))
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(
+            CassetteMode::from_args(None, Some("dir"), false),
+            CassetteMode::Replay(PathBuf::from("dir"))
+        );
+        assert_eq!(
+            CassetteMode::from_args(None, Some("dir"), true),
+            CassetteMode::ReplayFallback(PathBuf::from("dir"))
+        );
+        assert_eq!(
+            CassetteMode::from_args(Some("dir"), None, true),
+            CassetteMode::Record(PathBuf::from("dir"))
+        );
+    }
+}
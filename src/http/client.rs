@@ -1,11 +1,11 @@
 use std::borrow::Cow;
-use std::fs::{File, OpenOptions};
+use std::fs::File;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use cookie::Cookie;
 use rand::Rng;
 use regex::Regex;
@@ -13,19 +13,26 @@ use reqwest::cookie::{CookieStore, Jar};
 use reqwest::header::{
     HeaderMap, HeaderValue, CONTENT_TYPE, COOKIE, REFERER, SET_COOKIE, USER_AGENT,
 };
-use reqwest::{Client, Request, Response, Url};
+use reqwest::{Client, Proxy, Request, Response, StatusCode, Url};
 use serde_json::json;
 use serde_json::Value;
 
-use crate::http::crypto::{eapi, linuxapi, weapi, Crypto};
-use crate::http::request::{ApiRequest, Hm, UA};
-use crate::http::response::ApiResponse;
-use crate::http::store::{InMemStore, Store};
-use crate::http::{get_or_build_cookie_paths, request};
+use crate::config;
+use crate::http::cassette;
+use crate::http::crypto::{eapi, eapi_decrypt_response, linuxapi, weapi, Crypto};
+use crate::http::error::{ApiError, AUTH_CODES, RATE_LIMIT_CODES};
+use crate::http::request::{ApiRequest, ApiRequestBuilder, Hm, UA};
+use crate::http::response::{ApiResponse, ImplicitResult};
+use crate::http::retry::{rate_limiter, RetryPolicy};
+use crate::http::route::API_ROUTE;
+use crate::http::store::{FileStore, InMemStore, Store};
+use crate::http::transport::{HttpTransport, Transport, TransportRequest};
+use crate::http::{get_or_build_cookie_paths, get_or_build_http_cache_dir, request};
 
 pub struct ApiClient {
     config: Config,
     client: Client,
+    transport: Arc<dyn Transport>,
     jar: Arc<dyn CookieStore>,
     store: Box<dyn InMemStore>,
 }
@@ -46,36 +53,151 @@ impl ApiClient {
             .expect("build api client fail")
     }
 
+    // 基于当前客户端的cookie文件和传输层创建新的客户端，重新读取最新的cookie
+    // 沿用当前客户端的配置（包括api_base_url等），只重新设置缓存
+    fn derive(&self, cache: bool) -> ApiClientBuilder {
+        let defaults = ApiClientBuilder::new(&self.config.cookie_path, cache).config;
+        ApiClientBuilder {
+            config: Config {
+                cache,
+                cache_exp: defaults.cache_exp,
+                cache_dir: None,
+                transport: Some(self.transport.clone()),
+                ..self.config.clone()
+            },
+        }
+    }
+
     pub fn cache(&self, cache: bool) -> ApiClient {
-        let mut client = ApiClient::default();
-        client.config.cache = cache;
-        client
+        self.derive(cache).build().expect("build api client fail")
+    }
+
+    /// 使用持久化到磁盘的缓存，exp: 缓存有效期
+    pub fn persistent_cache(&self, exp: Duration) -> ApiClient {
+        let cache_dir = match &self.config.cache_dir {
+            Some(dir) => dir.clone(),
+            None => get_or_build_http_cache_dir()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+        };
+        self.derive(true)
+            .cache_exp(exp)
+            .cache_dir(&cache_dir)
+            .build()
+            .expect("build api client fail")
+    }
+
+    /// 使用给定的cookie而不是本地cookie文件，且不会自动写入本地，
+    /// 校验通过后需调用save_cookies保存
+    pub fn with_cookies(cookies: &str) -> Result<ApiClient> {
+        let path = get_or_build_cookie_paths()?;
+        ApiClientBuilder::new(&path.to_string_lossy(), false)
+            .cookies(cookies)
+            .preserve_cookies(false)
+            .build()
+    }
+
+    /// 将当前cookie写入本地cookie文件
+    pub fn save_cookies(&self) -> Result<()> {
+        if let Some(hv) = self.jar.cookies(&self.config.base_url) {
+            write_cookies(&self.config.cookie_path, hv.to_str()?)?;
+        }
+        Ok(())
     }
 
     pub fn login(&self, login: bool) -> ApiClient {
-        let mut client = ApiClient::default();
+        let mut client = self.cache(false);
         client.config.login = login;
         client
     }
 
     pub async fn request(&self, req: ApiRequest) -> Result<ApiResponse> {
+        if self.config.login {
+            return self.execute(req).await.map_err(network_error);
+        }
+
+        let res = self.execute(req.clone()).await.map_err(network_error)?;
+        if !is_auth_failure(&res) {
+            return Ok(res);
+        }
+        // 登录已过期，尝试刷新一次登录状态后重试
+        if self.refresh_login().await.map_err(network_error)? {
+            let res = self.execute(req).await.map_err(network_error)?;
+            if !is_auth_failure(&res) {
+                return Ok(res);
+            }
+        }
+        Err(ApiError::Auth.into())
+    }
+
+    async fn refresh_login(&self) -> Result<bool> {
+        let r = ApiRequestBuilder::post(API_ROUTE["login_refresh"]).build();
+        rate_limiter().acquire().await;
+        let req = TransportRequest {
+            route: r.url().to_owned(),
+            params: r.data().cloned().unwrap_or_else(|| json!({})),
+            request: self.to_http_request(r)?,
+        };
+        let resp = self.transport.execute(req).await?;
+        let res = self.on_login_response(resp).await?;
+        Ok(!is_auth_failure(&res)
+            && serde_json::from_slice::<ImplicitResult>(res.data())
+                .map(|r| r.code == 200)
+                .unwrap_or(false))
+    }
+
+    async fn execute(&self, req: ApiRequest) -> Result<ApiResponse> {
         let id = req.id();
 
-        if self.config.cache && self.store.contains_key(&id) {
-            return Ok(self.store.get(&id).unwrap());
+        if self.config.cache {
+            if let Some(res) = self.store.get(&id) {
+                return Ok(res);
+            }
         }
 
+        let idempotent = !self.config.login && req.is_idempotent();
+        let route = req.url().to_owned();
+        let params = req.data().cloned().unwrap_or_else(|| json!({}));
         let request = self.to_http_request(req)?;
         if self.config.log_request {
             println!("{:#?}", request);
         }
 
-        let resp = self.client.execute(request).await?;
+        let mut attempt = 0;
+        loop {
+            let req = TransportRequest {
+                route: route.clone(),
+                params: params.clone(),
+                request: request
+                    .try_clone()
+                    .ok_or_else(|| anyhow!("request can not be cloned"))?,
+            };
+            let result = self.send(req, &id).await;
+            let retryable = match &result {
+                Ok(res) => idempotent && is_transient_code(res),
+                Err(e) => is_transient_error(e, idempotent),
+            };
+            if !retryable || attempt >= self.config.retry.max_retries {
+                return result;
+            }
+            tokio::time::sleep(self.config.retry.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    async fn send(&self, req: TransportRequest, id: &str) -> Result<ApiResponse> {
+        rate_limiter().acquire().await;
+        let resp = self.transport.execute(req).await?;
         if self.config.log_response {
             println!("{:?}", resp);
         }
+        let status = resp.status();
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(resp.error_for_status().unwrap_err().into());
+        }
         return if !self.config.login {
-            self.on_response(id, resp).await
+            self.on_response(id.to_owned(), resp).await
         } else {
             self.on_login_response(resp).await
         };
@@ -83,16 +205,18 @@ impl ApiClient {
 
     async fn on_login_response(&self, resp: Response) -> Result<ApiResponse> {
         let headers = resp.headers().clone();
-        let url = resp.url().clone();
+        let url = self.cookie_url(resp.url()).clone();
         let mut cs = headers.get_all(SET_COOKIE).iter().peekable();
-        let body = resp.bytes().await?;
-        let res = ApiResponse::new(body.to_vec());
-        if cs.peek().is_some() && res.deserialize_to_implict().code == 200 {
+        let res = ApiResponse::new(read_body(resp).await?);
+        // 803 为扫码登录成功
+        let code = res.deserialize_to_implict().code;
+        if cs.peek().is_some() && (code == 200 || code == 803) {
             // sync cookie to jar
             self.jar.set_cookies(&mut cs, &url);
             // sync cookie to local
-            let hv = self.jar.cookies(&self.config.base_url).unwrap();
-            write_cookies(&self.config.cookie_path, hv.to_str().unwrap()).unwrap_or_default();
+            if self.config.preserve_cookies {
+                self.save_cookies().unwrap_or_default();
+            }
         }
         Ok(res)
     }
@@ -101,22 +225,29 @@ impl ApiClient {
         let mut cs = resp.headers().get_all(SET_COOKIE).iter().peekable();
         if cs.peek().is_some() {
             // sync cookie to jar
-            self.jar.set_cookies(&mut cs, resp.url());
+            self.jar.set_cookies(&mut cs, self.cookie_url(resp.url()));
             // sync cookie to local
-            let hv = self.jar.cookies(&self.config.base_url).unwrap();
-            write_cookies(&self.config.cookie_path, hv.to_str().unwrap()).unwrap_or_default();
+            if self.config.preserve_cookies {
+                self.save_cookies().unwrap_or_default();
+            }
         }
 
-        let body = resp.bytes().await?;
-        let res = ApiResponse::new(body.to_vec());
+        let res = ApiResponse::new(read_body(resp).await?);
 
-        if !self.config.cache {
+        // 只缓存成功的响应
+        let success = serde_json::from_slice::<ImplicitResult>(res.data())
+            .map(|r| r.code == 200)
+            .unwrap_or(false);
+        if !self.config.cache || !success {
             Ok(res)
         } else {
-            // cache response
-            self.store
-                .insert(id.clone(), res, Some(self.config.cache_exp));
-            Ok(self.store.get(&id).unwrap())
+            // cache response，写入缓存可能失败，直接返回已读取的响应
+            self.store.insert(
+                id,
+                ApiResponse::new(res.data().to_owned()),
+                Some(self.config.cache_exp),
+            );
+            Ok(res)
         }
     }
 
@@ -136,8 +267,10 @@ impl ApiClient {
         if url.contains("music.163.com") {
             headers.insert(REFERER, HeaderValue::from_static(BASE_URL));
         }
-        if let Some(real_ip) = real_ip {
-            headers.insert("X-Real-IP", HeaderValue::try_from(real_ip).unwrap());
+        if let Some(real_ip) = real_ip.or_else(|| self.config.real_ip.clone()) {
+            let value = HeaderValue::try_from(real_ip.as_str())
+                .map_err(|_| anyhow!("X-Real-IP无效：{}", real_ip))?;
+            headers.insert("X-Real-IP", value);
         }
 
         // COOKIE header might be overrided by the cookie_store according to
@@ -249,7 +382,10 @@ impl ApiClient {
         // request builder
         let rb = self
             .client
-            .request(map_method(method), adapt_url(&url, crypto).parse::<Url>()?)
+            .request(
+                map_method(method),
+                self.rebase_url(&adapt_url(&url, crypto))?,
+            )
             .headers(headers)
             .form(&form_data);
 
@@ -271,6 +407,31 @@ impl ApiClient {
         cs
     }
 
+    // 配置了api_base_url时替换请求地址的协议、域名和端口
+    fn rebase_url(&self, url: &str) -> Result<Url> {
+        let url = url.parse::<Url>()?;
+        let base = match &self.config.api_base_url {
+            Some(base) => base,
+            None => return Ok(url),
+        };
+        let mut rebased = base.clone();
+        rebased.set_path(&format!(
+            "{}{}",
+            base.path().trim_end_matches('/'),
+            url.path()
+        ));
+        rebased.set_query(url.query());
+        Ok(rebased)
+    }
+
+    // 兼容服务返回的cookie按网易云域名保存，保证之后的请求能带上
+    fn cookie_url<'a>(&'a self, url: &'a Url) -> &'a Url {
+        match self.config.api_base_url {
+            Some(_) => &self.config.base_url,
+            None => url,
+        }
+    }
+
     pub fn base_url(&self) -> &Url {
         &self.config.base_url
     }
@@ -379,6 +540,7 @@ pub struct ApiClientBuilder {
 
 impl ApiClientBuilder {
     pub fn new(cookie_path: &str, cache: bool) -> Self {
+        let network = config::network::get();
         ApiClientBuilder {
             config: Config {
                 cache,
@@ -387,9 +549,18 @@ impl ApiClientBuilder {
                 base_url: BASE_URL.parse::<Url>().unwrap(),
                 preserve_cookies: true,
                 cookie_path: String::from(cookie_path),
+                cookies: None,
+                cache_dir: None,
                 log_request: false,
                 log_response: false,
                 login: false,
+                proxy: network.api_proxy().map(String::from),
+                real_ip: network.real_ip().map(String::from),
+                timeout: network.timeout(),
+                connect_timeout: network.connect_timeout(),
+                retry: network.retry_policy(),
+                transport: None,
+                api_base_url: network.api_base_url().and_then(|u| u.parse::<Url>().ok()),
             },
         }
     }
@@ -400,22 +571,41 @@ impl ApiClientBuilder {
         let jar = Arc::new(Jar::default());
 
         // sync cookies
-        if let Ok(cs) = read_cookies(&config.cookie_path) {
-            if !cs.is_empty() {
-                let ch = cs
-                    .split("; ")
-                    .map(|cookie| HeaderValue::from_str(cookie).unwrap())
-                    .collect::<Vec<_>>();
+        let cs = match &config.cookies {
+            Some(cs) => Ok(cs.clone()),
+            None => read_cookies(&config.cookie_path),
+        };
+        if let Ok(cs) = cs {
+            let ch = cookie_header_values(&cs)?;
+            if !ch.is_empty() {
                 let mut cs = ch.iter();
                 jar.set_cookies(&mut cs, &config.base_url);
             }
         }
 
+        let mut client = Client::builder()
+            .cookie_store(false)
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout);
+        if let Some(proxy) = &config.proxy {
+            client = client.proxy(Proxy::all(proxy)?);
+        }
+
+        let client = client.build()?;
+        let transport = match &config.transport {
+            Some(transport) => transport.clone(),
+            None => cassette::wrap(Arc::new(HttpTransport::new(client.clone()))),
+        };
+
         Ok(ApiClient {
-            config,
-            client: Client::builder().cookie_store(false).build().unwrap(),
-            store: Box::new(Store::new(ci)),
+            client,
+            transport,
+            store: match &config.cache_dir {
+                Some(dir) => Box::new(FileStore::new(PathBuf::from(dir))),
+                None => Box::new(Store::new(ci)),
+            },
             jar,
+            config,
         })
     }
 
@@ -425,7 +615,6 @@ impl ApiClientBuilder {
         self
     }
 
-    #[allow(unused)]
     pub fn cache_exp(mut self, exp: Duration) -> Self {
         self.config.cache_exp = exp;
         self
@@ -437,7 +626,17 @@ impl ApiClientBuilder {
         self
     }
 
-    #[allow(unused)]
+    /// 设置后缓存会持久化到该目录
+    pub fn cache_dir(mut self, dir: &str) -> Self {
+        self.config.cache_dir = Some(dir.to_owned());
+        self
+    }
+
+    pub fn cookies(mut self, cookies: &str) -> Self {
+        self.config.cookies = Some(cookies.to_owned());
+        self
+    }
+
     pub fn preserve_cookies(mut self, enable: bool) -> Self {
         self.config.preserve_cookies = enable;
         self
@@ -455,6 +654,52 @@ impl ApiClientBuilder {
         self
     }
 
+    /// 代理地址，支持http、https和socks5
+    #[allow(unused)]
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.config.proxy = Some(proxy.to_owned());
+        self
+    }
+
+    #[allow(unused)]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    #[allow(unused)]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = timeout;
+        self
+    }
+
+    #[allow(unused)]
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.config.retry = retry;
+        self
+    }
+
+    /// 替换发送请求的传输层，如测试时使用FixtureTransport
+    #[allow(unused)]
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.config.transport = Some(transport);
+        self
+    }
+
+    /// 所有请求默认携带的X-Real-IP
+    #[allow(unused)]
+    pub fn real_ip(mut self, real_ip: &str) -> Self {
+        self.config.real_ip = Some(real_ip.to_owned());
+        self
+    }
+
+    /// 接口请求发往的地址，接口路径不变
+    #[allow(unused)]
+    pub fn api_base_url(mut self, url: &str) -> Result<Self> {
+        self.config.api_base_url = Some(url.parse::<Url>()?);
+        Ok(self)
+    }
+
     #[allow(unused)]
     pub fn cookie_path(mut self, path: &str) -> Self {
         self.config.cookie_path = path.to_owned();
@@ -462,35 +707,146 @@ impl ApiClientBuilder {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Config {
     #[allow(unused)]
     cache: bool,
     cache_exp: Duration,
     cache_clean_interval: Duration,
 
-    #[allow(unused)]
     preserve_cookies: bool,
 
     cookie_path: String,
+    // 设置后使用该cookie而不读取cookie文件
+    cookies: Option<String>,
+    cache_dir: Option<String>,
     base_url: Url,
 
     log_request: bool,
     log_response: bool,
 
     login: bool,
+
+    proxy: Option<String>,
+    real_ip: Option<String>,
+    timeout: Duration,
+    connect_timeout: Duration,
+    retry: RetryPolicy,
+    transport: Option<Arc<dyn Transport>>,
+    // 设置后接口请求发往该地址，cookie仍按网易云域名保存
+    api_base_url: Option<Url>,
 }
 
-fn write_cookies(path: &str, cs: &str) -> Result<()> {
-    if !Path::new(path).exists() {
-        File::create(path)?;
+// 读取响应体，eapi加密的响应体解密后返回
+async fn read_body(resp: Response) -> Result<Vec<u8>> {
+    let body = resp.bytes().await?;
+    Ok(eapi_decrypt_response(&body).unwrap_or_else(|| body.to_vec()))
+}
+
+// 未登录或登录已过期
+fn is_auth_failure(res: &ApiResponse) -> bool {
+    serde_json::from_slice::<ImplicitResult>(res.data())
+        .map(|r| AUTH_CODES.contains(&r.code))
+        .unwrap_or(false)
+}
+
+// 服务端繁忙或触发频率限制，稍后重试可能成功
+fn is_transient_code(res: &ApiResponse) -> bool {
+    serde_json::from_slice::<Value>(res.data())
+        .ok()
+        .and_then(|v| v.get("code").and_then(Value::as_i64))
+        .map(|code| RATE_LIMIT_CODES.contains(&code))
+        .unwrap_or(false)
+}
+
+// 连接失败时请求未发出，总是可以重试；超时和服务端错误只重试幂等请求
+fn is_transient_error(e: &anyhow::Error, idempotent: bool) -> bool {
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(e) if e.is_connect() => true,
+        Some(e) => idempotent && (e.is_timeout() || e.is_status()),
+        None => false,
+    }
+}
+
+// 重试后仍失败的请求错误转为ApiError，便于界面显示具体原因
+fn network_error(e: anyhow::Error) -> anyhow::Error {
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(re) if re.status() == Some(StatusCode::TOO_MANY_REQUESTS) => {
+            ApiError::RateLimited.into()
+        }
+        Some(re) => ApiError::Network(re.to_string()).into(),
+        None => e,
     }
-    let mut file = OpenOptions::new().write(true).open(path)?;
+}
+
+fn write_cookies(path: &str, cs: &str) -> Result<()> {
+    // 覆盖写入，避免新cookie比旧的短时残留旧内容
+    let mut file = File::create(path)?;
 
     file.write_all(cs.as_bytes())?;
     Ok(())
 }
 
+/// 解析用户导入的cookie，支持：
+/// 1. Netscape格式的cookies.txt文件路径
+/// 2. 从浏览器复制的cookie字符串，如 "MUSIC_U=xxx; __csrf=xxx"
+/// 3. 单独的MUSIC_U值
+pub(crate) fn parse_cookies(input: &str) -> Result<String> {
+    let input = input.trim();
+    let pairs = if Path::new(input).is_file() {
+        let mut content = String::new();
+        File::open(input)?.read_to_string(&mut content)?;
+        parse_netscape_cookies(&content)
+    } else if input.contains('=') {
+        input
+            .split(';')
+            .map(|c| c.trim())
+            .filter(|c| c.contains('='))
+            .map(|c| c.to_owned())
+            .collect::<Vec<_>>()
+    } else if !input.is_empty() {
+        vec![format!("MUSIC_U={}", input)]
+    } else {
+        vec![]
+    };
+
+    if !pairs.iter().any(|c| c.starts_with("MUSIC_U=")) {
+        return Err(anyhow!("Cookie中缺少MUSIC_U"));
+    }
+    let cookies = pairs.join("; ");
+    // 提前校验，避免带有控制字符的cookie在创建客户端时出错
+    cookie_header_values(&cookies)?;
+    Ok(cookies)
+}
+
+// 拆分"a=1; b=2"形式的cookie，分号后可以没有空格，含有非法字符时返回错误
+fn cookie_header_values(cookies: &str) -> Result<Vec<HeaderValue>> {
+    cookies
+        .trim()
+        .split(';')
+        .map(|c| c.trim())
+        .filter(|c| !c.is_empty())
+        .map(|c| HeaderValue::from_str(c).map_err(|_| anyhow!("Cookie无效")))
+        .collect()
+}
+
+// 解析Netscape格式的cookies.txt，只保留网易云音乐域名下的cookie
+fn parse_netscape_cookies(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.trim_start_matches("#HttpOnly_"))
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter_map(|line| {
+            let fields = line.split('\t').collect::<Vec<_>>();
+            if fields.len() == 7 && fields[0].ends_with("music.163.com") {
+                Some(format!("{}={}", fields[5], fields[6].trim_end()))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
 fn read_cookies(path: &str) -> Result<String> {
     let mut file = File::open(path)?;
     let mut cs = String::new();
@@ -563,6 +919,7 @@ mod tests {
 
     use crate::http::request::Hm;
     use crate::http::route::API_ROUTE;
+    use crate::http::transport::{FixtureTransport, WithTempDir};
 
     use super::*;
 
@@ -615,6 +972,166 @@ mod tests {
         assert!(http_req.is_ok());
     }
 
+    fn fixture_client(transport: Arc<FixtureTransport>) -> WithTempDir<ApiClient> {
+        WithTempDir::new(|dir| {
+            ApiClientBuilder::new(dir.join("cookies").to_str().unwrap(), true)
+                .transport(transport)
+                .build()
+                .unwrap()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_request() {
+        let transport = Arc::new(FixtureTransport::new().route_with_params(
+            API_ROUTE["cloudsearch"],
+            json!({ "s": "mota", "limit": 1 }),
+            json!({ "code": 200, "result": { "songs": [] } }),
+        ));
+        let c = fixture_client(transport);
+        let resp = c.request(create_search_req()).await.unwrap();
+        assert_eq!(resp.deserialize_to_implict().code, 200);
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let transport = Arc::new(
+            FixtureTransport::new().route(API_ROUTE["cloudsearch"], json!({ "code": 200 })),
+        );
+        let c = fixture_client(transport.clone());
+        for _ in 0..3 {
+            let resp = c.request(create_search_req()).await.unwrap();
+            assert_eq!(resp.deserialize_to_implict().code, 200);
+        }
+        // 后两次请求命中缓存
+        assert_eq!(transport.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_auth_failure() {
+        let transport = Arc::new(
+            FixtureTransport::new()
+                .route(API_ROUTE["user_account"], json!({ "code": 301 }))
+                .route(API_ROUTE["login_refresh"], json!({ "code": 301 })),
+        );
+        let c = fixture_client(transport.clone());
+        let r = Rb::post(API_ROUTE["user_account"]).build();
+        let err = c.request(r).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ApiError>(),
+            Some(ApiError::Auth)
+        ));
+        // 刷新登录失败后不再重试原请求
+        assert_eq!(transport.request_count(), 2);
+    }
+
+    #[test]
+    fn test_api_base_url() {
+        let c = ApiClientBuilder::new(COOKIE_PATH, false)
+            .api_base_url("http://127.0.0.1:3000/proxy/")
+            .unwrap()
+            .build()
+            .unwrap();
+        let http_req = c.to_http_request(create_search_req()).unwrap();
+        assert_eq!(
+            http_req.url().as_str(),
+            "http://127.0.0.1:3000/proxy/weapi/cloudsearch/pc"
+        );
+
+        // 派生的客户端沿用同样的设置
+        for c in [c.cache(false), c.cache(true)] {
+            let http_req = c.to_http_request(create_search_req()).unwrap();
+            assert_eq!(
+                http_req.url().as_str(),
+                "http://127.0.0.1:3000/proxy/weapi/cloudsearch/pc"
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_cookies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cookies");
+        let path = path.to_str().unwrap();
+        assert!(write_cookies(path, "name=alex; age=19").is_ok());
+        assert_eq!(read_cookies(path).unwrap(), "name=alex; age=19");
+    }
+
+    #[test]
+    fn test_parse_cookies() {
+        let cs = parse_cookies(" MUSIC_U=abc; __csrf=def ; ").unwrap();
+        assert_eq!(cs, "MUSIC_U=abc; __csrf=def");
+
+        let cs = parse_cookies("abc").unwrap();
+        assert_eq!(cs, "MUSIC_U=abc");
+
+        assert!(parse_cookies("__csrf=def").is_err());
+        assert!(parse_cookies("").is_err());
+
+        // 粘贴时带有的换行会被去掉，值中的控制字符返回错误而不是panic
+        assert_eq!(parse_cookies("MUSIC_U=abc\n").unwrap(), "MUSIC_U=abc");
+        assert_eq!(
+            parse_cookies("MUSIC_U=a\u{1}bc").unwrap_err().to_string(),
+            "Cookie无效"
+        );
+    }
+
+    #[test]
+    fn test_cookie_header_values() {
+        let cs = cookie_header_values("a=1;b=2").unwrap();
+        assert_eq!(cs, vec!["a=1", "b=2"]);
+        let cs = cookie_header_values(" a=1; b=2;\n").unwrap();
+        assert_eq!(cs.len(), 2);
+        assert!(cookie_header_values("").unwrap().is_empty());
+        assert!(cookie_header_values("a=1\u{7f}").is_err());
+    }
+
+    #[test]
+    fn test_parse_netscape_cookies() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "# Netscape HTTP Cookie File").unwrap();
+        writeln!(
+            file,
+            "#HttpOnly_.music.163.com\tTRUE\t/\tFALSE\t0\tMUSIC_U\tabc"
+        )
+        .unwrap();
+        writeln!(file, ".music.163.com\tTRUE\t/\tFALSE\t0\t__csrf\tdef").unwrap();
+        writeln!(file, ".example.com\tTRUE\t/\tFALSE\t0\tsid\txyz").unwrap();
+
+        let cs = parse_cookies(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(cs, "MUSIC_U=abc; __csrf=def");
+    }
+
+    #[test]
+    fn test_eapi_headers() {
+        let c = ApiClientBuilder::new(COOKIE_PATH, false).build().unwrap();
+
+        let c = c.eapi_header_cookies();
+        println!("{}", c.get("requestId").unwrap());
+    }
+}
+
+#[cfg(all(test, feature = "live-tests"))]
+mod live_tests {
+    use serde_json::json;
+
+    use crate::http::route::API_ROUTE;
+
+    use super::*;
+
+    const COOKIE_PATH: &str = "D:/workspace/ncmapi_client_cookies";
+
+    fn create_search_req() -> ApiRequest {
+        request::ApiRequestBuilder::post(API_ROUTE["cloudsearch"])
+            .set_data(json!({
+                "s": "mota",
+                "type": 1,
+            }))
+            .insert("offset", json!(0))
+            .merge(json!({"limit": 1}))
+            .build()
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_request() {
         let c = ApiClientBuilder::new(COOKIE_PATH, false)
@@ -656,24 +1173,4 @@ mod tests {
         assert_eq!(res.code, 200);
         std::thread::sleep(std::time::Duration::from_secs(10));
     }
-
-    #[test]
-    fn test_read_cookies() {
-        let res = read_cookies(COOKIE_PATH);
-        assert!(res.is_ok());
-    }
-
-    #[test]
-    fn test_write_cookies() {
-        let res = write_cookies(COOKIE_PATH, "name=alex; age=19");
-        assert!(res.is_ok())
-    }
-
-    #[test]
-    fn test_eapi_headers() {
-        let c = ApiClientBuilder::new(COOKIE_PATH, false).build().unwrap();
-
-        let c = c.eapi_header_cookies();
-        println!("{}", c.get("requestId").unwrap());
-    }
 }
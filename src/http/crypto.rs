@@ -100,11 +100,27 @@ pub fn eapi(url: &[u8], data: &[u8]) -> EapiForm {
     EapiForm { params }
 }
 
-#[allow(unused)]
 pub fn eapi_decrypt(ct: &[u8]) -> Result<Vec<u8>, ErrorStack> {
     aes_128_ecb_decrypt(ct, EAPI_KEY.as_bytes(), None)
 }
 
+/// eapi请求带有e_r时响应体是加密的，可能是二进制或十六进制字符串。
+/// 响应体已经是json或无法解密为json时返回None
+pub fn eapi_decrypt_response(body: &[u8]) -> Option<Vec<u8>> {
+    if body.is_empty() || is_json(body) {
+        return None;
+    }
+    let ct = hex::decode(body).unwrap_or_else(|_| body.to_vec());
+    if !ct.len().is_multiple_of(16) {
+        return None;
+    }
+    eapi_decrypt(&ct).ok().filter(|pt| is_json(pt))
+}
+
+fn is_json(body: &[u8]) -> bool {
+    serde_json::from_slice::<serde::de::IgnoredAny>(body).is_ok()
+}
+
 pub fn linuxapi(text: &[u8]) -> LinuxapiForm {
     let ct = aes_128_ecb(text, LINUX_API_KEY.as_bytes(), None);
     let eparams = hex::encode_upper(ct);
@@ -141,7 +157,7 @@ fn rsa(pt: &[u8], key: &[u8]) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
     use super::{aes_128_cbc, aes_128_ecb, aes_128_ecb_decrypt, rsa, weapi};
-    use crate::http::crypto::{eapi, eapi_decrypt, linuxapi};
+    use crate::http::crypto::{eapi, eapi_decrypt, eapi_decrypt_response, linuxapi};
     use crate::http::key::{EAPI_KEY, IV, PRESET_KEY, PUBLIC_KEY};
 
     #[test]
@@ -187,6 +203,22 @@ mod tests {
         assert_eq!(pt.as_bytes(), &eapi_decrypt(&ct).unwrap())
     }
 
+    #[test]
+    fn test_eapi_decrypt_response() {
+        let pt = r#"{"code":200}"#;
+        let ct = aes_128_ecb(pt.as_bytes(), EAPI_KEY.as_bytes(), None);
+        assert_eq!(eapi_decrypt_response(&ct).unwrap(), pt.as_bytes());
+        assert_eq!(
+            eapi_decrypt_response(hex::encode_upper(&ct).as_bytes()).unwrap(),
+            pt.as_bytes()
+        );
+        // 未加密的响应不处理
+        assert!(eapi_decrypt_response(pt.as_bytes()).is_none());
+        assert!(eapi_decrypt_response(b"").is_none());
+        let ct = aes_128_ecb(b"plain text", EAPI_KEY.as_bytes(), None);
+        assert!(eapi_decrypt_response(&ct).is_none());
+    }
+
     #[test]
     fn test_linuxapi() {
         let ct = linuxapi(r#""plain text""#.as_bytes());
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// 未登录或登录已过期
+pub const AUTH_CODES: [i64; 1] = [301];
+// 操作频繁或网络拥挤，稍后重试可能成功
+pub const RATE_LIMIT_CODES: [i64; 3] = [405, 429, -460];
+// 因版权或合作方要求资源不可用
+const COPYRIGHT_CODES: [i64; 1] = [-200];
+
+// 需要调用方特殊处理的接口错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    // 登录已过期且刷新登录失败，需要重新登录
+    Auth,
+    // 请求过于频繁，被服务端限流
+    RateLimited,
+    // 因版权限制无法获取
+    Copyright,
+    // 网络连接失败或服务端错误
+    Network(String),
+    // 响应不是预期的格式，msg为该接口的默认信息
+    Decode(String),
+    // 接口返回的其他错误，msg为服务端返回的信息或该接口的默认信息
+    Api { code: i64, msg: String },
+}
+
+impl ApiError {
+    /// 根据接口返回的code区分错误类型
+    pub fn from_code(code: i64, msg: String) -> Self {
+        if AUTH_CODES.contains(&code) {
+            ApiError::Auth
+        } else if RATE_LIMIT_CODES.contains(&code) {
+            ApiError::RateLimited
+        } else if COPYRIGHT_CODES.contains(&code) {
+            ApiError::Copyright
+        } else {
+            ApiError::Api { code, msg }
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Auth => write!(f, "登录已过期，请重新登录"),
+            ApiError::RateLimited => write!(f, "操作过于频繁，请稍后再试"),
+            ApiError::Copyright => write!(f, "因版权限制，暂时无法获取"),
+            ApiError::Network(e) => write!(f, "网络连接错误：{}", e),
+            ApiError::Decode(msg) => write!(f, "{}", msg),
+            ApiError::Api { msg, .. } => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiError;
+
+    #[test]
+    fn test_from_code() {
+        assert_eq!(ApiError::from_code(301, String::new()), ApiError::Auth);
+        assert_eq!(
+            ApiError::from_code(-460, String::new()),
+            ApiError::RateLimited
+        );
+        assert_eq!(
+            ApiError::from_code(-200, String::new()),
+            ApiError::Copyright
+        );
+        // 404 可能只是资源不存在，不视为版权限制
+        assert_eq!(
+            ApiError::from_code(404, "歌单不存在".to_string()),
+            ApiError::Api {
+                code: 404,
+                msg: "歌单不存在".to_string()
+            }
+        );
+        assert_eq!(
+            ApiError::from_code(501, "邮箱账号不存在".to_string()).to_string(),
+            "邮箱账号不存在"
+        );
+    }
+}
@@ -1,23 +1,33 @@
 use crate::cli::clap::BANNER;
+use crate::http::account::AccountManager;
 use crate::http::api::CloudMusicApi;
+use crate::model::login::DEFAULT_COUNTRY_CODE;
 use anyhow::anyhow;
 use anyhow::Result;
 use std::fs;
 use std::io::stdin;
 use std::path::{Path, PathBuf};
 
+pub(crate) mod account;
 pub(crate) mod api;
+pub(crate) mod cassette;
 mod client;
 mod crypto;
+pub(crate) mod error;
 mod key;
 mod request;
-mod response;
-mod route;
+pub(crate) mod response;
+pub(crate) mod retry;
+pub(crate) mod route;
 mod store;
+pub(crate) mod transport;
 
 const CONFIG_DIR: &str = ".config";
 const APP_CONFIG_DIR: &str = "rs-music-tui";
 const COOKIE_FILE_NAME: &str = "cookie";
+const HTTP_CACHE_DIR_NAME: &str = "http_cache";
+const COUNTRY_CODE_FILE_NAME: &str = "country_code";
+const ACCOUNTS_DIR_NAME: &str = "accounts";
 
 #[allow(unused)]
 pub async fn login_phone() -> Result<()> {
@@ -31,7 +41,7 @@ pub async fn login_phone() -> Result<()> {
             let phone = receive_key_from_input("phone")?;
             let password = receive_key_from_input("password")?;
             let resp = CloudMusicApi::default()
-                .login_phone(&phone, &password)
+                .login_phone(DEFAULT_COUNTRY_CODE, &phone, &password)
                 .await;
             if resp.is_err() {
                 println!("登录失败");
@@ -64,6 +74,58 @@ fn receive_key_from_input(type_label: &'static str) -> Result<String> {
 }
 
 pub fn get_or_build_cookie_paths() -> Result<PathBuf> {
+    let app_config_dir = get_or_build_app_config_dir()?;
+    Ok(app_config_dir.join(COOKIE_FILE_NAME))
+}
+
+// 持久化的接口响应缓存目录
+pub fn get_or_build_http_cache_dir() -> Result<PathBuf> {
+    let app_config_dir = get_or_build_app_config_dir()?;
+    let cache_dir = app_config_dir.join(HTTP_CACHE_DIR_NAME);
+    if !cache_dir.exists() {
+        fs::create_dir(&cache_dir)?;
+    }
+    Ok(cache_dir)
+}
+
+pub fn account_manager() -> Result<AccountManager> {
+    let app_config_dir = get_or_build_app_config_dir()?;
+    Ok(AccountManager::new(
+        app_config_dir.join(ACCOUNTS_DIR_NAME),
+        app_config_dir.join(COOKIE_FILE_NAME),
+    ))
+}
+
+// 清除持久化的接口响应缓存
+pub fn clear_http_cache() -> Result<()> {
+    let cache_dir = get_or_build_app_config_dir()?.join(HTTP_CACHE_DIR_NAME);
+    if cache_dir.exists() {
+        fs::remove_dir_all(cache_dir)?;
+    }
+    Ok(())
+}
+
+// 读取上次登录使用的国家码
+pub fn read_country_code() -> Option<String> {
+    let path = get_or_build_app_config_dir()
+        .ok()?
+        .join(COUNTRY_CODE_FILE_NAME);
+    let code = fs::read_to_string(path).ok()?;
+    let code = code.trim();
+    if code.is_empty() {
+        None
+    } else {
+        Some(code.to_string())
+    }
+}
+
+pub fn write_country_code(code: &str) -> Result<()> {
+    let path = get_or_build_app_config_dir()?.join(COUNTRY_CODE_FILE_NAME);
+    fs::write(path, code)?;
+    Ok(())
+}
+
+fn get_or_build_app_config_dir() -> Result<PathBuf> {
     match dirs::home_dir() {
         Some(home) => {
             let path = Path::new(&home);
@@ -78,10 +140,7 @@ pub fn get_or_build_cookie_paths() -> Result<PathBuf> {
                 fs::create_dir(&app_config_dir)?;
             }
 
-            let cookie_file_path = &app_config_dir.join(COOKIE_FILE_NAME);
-
-            let paths = cookie_file_path.to_path_buf();
-            Ok(paths)
+            Ok(app_config_dir)
         }
         None => Err(anyhow!("No $HOME directory found for client config")),
     }
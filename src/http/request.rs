@@ -7,7 +7,7 @@ use std::collections::HashMap;
 
 pub(crate) type Hm = HashMap<String, String>;
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct ApiRequest {
     method: Method,
     url: String,
@@ -38,6 +38,19 @@ impl ApiRequest {
         serde_json::to_string(self).unwrap()
     }
 
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn data(&self) -> Option<&Value> {
+        self.data.as_ref()
+    }
+
+    /// 重复发送不会产生副作用的请求，失败后可以自动重试
+    pub fn is_idempotent(&self) -> bool {
+        self.option.idempotent
+    }
+
     pub fn id(&self) -> String {
         let digest = hash(MessageDigest::md5(), self.serialize().as_bytes()).unwrap();
         hex::encode(digest)
@@ -66,11 +79,13 @@ impl ApiRequestBuilder {
                 crypto: Crypto::Weapi,
                 api_url: None,
                 real_ip: None,
+                idempotent: true,
             },
         }
     }
 
     pub fn build(self) -> ApiRequest {
+        let idempotent = self.config.idempotent;
         let (method, url, data, ua, cookies, crypto, api_url, real_ip) = self.pieces();
         ApiRequest {
             method,
@@ -82,6 +97,7 @@ impl ApiRequestBuilder {
                 crypto,
                 api_url,
                 real_ip,
+                idempotent,
             },
         }
     }
@@ -168,6 +184,12 @@ impl ApiRequestBuilder {
         self.config.real_ip = Some(String::from(real_ip));
         self
     }
+
+    /// 标记有副作用的请求（如收藏、发送验证码），超时后不会自动重试
+    pub fn set_idempotent(mut self, idempotent: bool) -> Self {
+        self.config.idempotent = idempotent;
+        self
+    }
 }
 
 type Pieces = (
@@ -191,6 +213,7 @@ struct Config {
     crypto: Crypto,
     api_url: Option<String>,
     real_ip: Option<String>,
+    idempotent: bool,
 }
 
 #[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
@@ -207,13 +230,16 @@ pub enum Method {
     Patch,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct RequestOption {
     ua: UA,
     cookies: Option<Hm>,
     crypto: Crypto,
     api_url: Option<String>,
     real_ip: Option<String>,
+    // 不参与请求id计算
+    #[serde(skip)]
+    idempotent: bool,
 }
 
 #[derive(Serialize, Debug, Clone, Copy)]
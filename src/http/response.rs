@@ -1,8 +1,18 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
+use crate::http::error::ApiError;
+
+/// 接口的响应类型，code和msg由ApiResponse::parse统一处理
+pub trait ApiResp: DeserializeOwned {
+    // 接口失败且服务端没有返回信息时显示的错误
+    const ERROR: &'static str;
+}
+
 pub struct ApiResponse {
     data: Vec<u8>,
 }
@@ -23,6 +33,26 @@ impl ApiResponse {
     pub fn deserialize_to_implict(&self) -> ImplicitResult {
         serde_json::from_slice::<ImplicitResult>(self.data()).unwrap()
     }
+
+    /// 检查code后解析为对应接口的响应类型，code不为200时返回ApiError
+    pub fn parse<T: ApiResp>(&self) -> Result<T> {
+        self.parse_or(T::ERROR)
+    }
+
+    /// 同parse，服务端没有返回错误信息时使用error
+    pub fn parse_or<T: DeserializeOwned>(&self, error: &str) -> Result<T> {
+        let status = serde_json::from_slice::<ImplicitResult>(self.data())
+            .map_err(|_| ApiError::Decode(error.to_owned()))?;
+        if status.code != 200 {
+            let msg = [&status.msg, &status.message]
+                .into_iter()
+                .find_map(|m| m.as_str().filter(|m| !m.is_empty()))
+                .unwrap_or(error);
+            return Err(ApiError::from_code(status.code, msg.to_owned()).into());
+        }
+        Ok(serde_json::from_slice::<T>(self.data())
+            .map_err(|_| ApiError::Decode(error.to_owned()))?)
+    }
 }
 
 impl Display for ApiResponse {
@@ -42,7 +72,7 @@ impl fmt::Debug for ApiResponse {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ImplicitResult {
     #[serde(default)]
-    pub code: usize,
+    pub code: i64,
 
     #[serde(default)]
     pub msg: Value,
@@ -59,3 +89,57 @@ pub struct ImplicitResult {
     #[serde(default)]
     pub data: Value,
 }
+
+impl ApiResp for ImplicitResult {
+    const ERROR: &'static str = "操作失败";
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ApiResponse, ImplicitResult};
+    use crate::http::error::ApiError;
+
+    fn response(body: serde_json::Value) -> ApiResponse {
+        ApiResponse::new(body.to_string().into_bytes())
+    }
+
+    #[test]
+    fn test_parse() {
+        let res = response(json!({ "code": 200, "data": 1 }))
+            .parse::<ImplicitResult>()
+            .unwrap();
+        assert_eq!(res.data, json!(1));
+
+        let err = response(json!({ "code": -460, "message": "Cheating" }))
+            .parse::<ImplicitResult>()
+            .unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&ApiError::RateLimited));
+
+        // 优先使用服务端返回的信息
+        let err = response(json!({ "code": 502, "msg": "密码错误" }))
+            .parse_or::<ImplicitResult>("登录失败")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "密码错误");
+        let err = response(json!({ "code": 400 }))
+            .parse_or::<ImplicitResult>("登录失败")
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref(),
+            Some(&ApiError::Api {
+                code: 400,
+                msg: "登录失败".to_string()
+            })
+        );
+
+        // 响应无法解析时不是网络错误
+        let err = ApiResponse::new(b"<html></html>".to_vec())
+            .parse_or::<ImplicitResult>("登录失败")
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref(),
+            Some(&ApiError::Decode("登录失败".to_string()))
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::config;
+
+static RATE_LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+/// 重试策略，第n次重试前等待 base_delay * 2^n（不超过max_delay），
+/// 并在其一半到全部之间随机抖动，避免多个请求同时重试
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(300),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = delay / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+}
+
+/// 令牌桶限流，桶容量为burst，每秒补充rate个令牌
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u32, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            rate: f64::from(rate.max(1)),
+            burst,
+            state: Mutex::new((burst, Instant::now())),
+        }
+    }
+
+    /// 取一个令牌，没有令牌时等待补充
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let (tokens, last) = *state;
+                let now = Instant::now();
+                let tokens =
+                    (tokens + now.duration_since(last).as_secs_f64() * self.rate).min(self.burst);
+                if tokens >= 1.0 {
+                    *state = (tokens - 1.0, now);
+                    return;
+                }
+                *state = (tokens, now);
+                Duration::from_secs_f64((1.0 - tokens) / self.rate)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// 所有接口请求共享的限流器
+pub fn rate_limiter() -> &'static RateLimiter {
+    RATE_LIMITER.get_or_init(|| {
+        let network = config::network::get();
+        RateLimiter::new(network.rate_limit(), network.rate_limit_burst())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{RateLimiter, RetryPolicy};
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        };
+        for attempt in 0..5 {
+            let expected =
+                (Duration::from_millis(100) * 2u32.pow(attempt)).min(Duration::from_millis(500));
+            let delay = policy.backoff(attempt);
+            assert!(delay >= expected / 2 && delay <= expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(20, 2);
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire().await;
+        }
+        // 前2个令牌立即可用，后2个需要等待补充
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+}
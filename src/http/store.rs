@@ -1,11 +1,14 @@
 use memory_cache::MemoryCache;
 
 use crate::http::response::ApiResponse;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{sync::RwLock, time};
 
-pub(crate) trait InMemStore {
+pub(crate) trait InMemStore: Send + Sync {
     fn get(&self, id: &str) -> Option<ApiResponse>;
-    fn contains_key(&self, id: &str) -> bool;
     fn insert(
         &self,
         id: String,
@@ -30,8 +33,61 @@ impl InMemStore for Store {
         None
     }
 
-    fn contains_key(&self, id: &str) -> bool {
-        self.0.read().unwrap().contains_key(&String::from(id))
+    fn insert(
+        &self,
+        id: String,
+        val: ApiResponse,
+        lifetime: Option<time::Duration>,
+    ) -> Option<ApiResponse> {
+        self.0.write().unwrap().insert(id, val, lifetime)
+    }
+}
+
+/// 基于文件的持久化缓存，每个请求对应缓存目录下的一个文件，
+/// 文件第一行为过期时间（unix秒，0表示永不过期），其后为响应内容
+pub(crate) struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(id)
+    }
+
+    // 读取未过期的缓存内容，过期的缓存文件会被删除
+    fn read(&self, id: &str) -> Option<Vec<u8>> {
+        let path = self.path(id);
+        let content = fs::read(&path).ok()?;
+        let pos = content.iter().position(|b| *b == b'\n')?;
+        let expire = String::from_utf8_lossy(&content[..pos])
+            .parse::<u64>()
+            .ok()?;
+        if expire != 0 && expire <= now_secs() {
+            fs::remove_file(&path).ok();
+            return None;
+        }
+        Some(content[pos + 1..].to_vec())
+    }
+}
+
+impl FileStore {
+    // 先写入临时文件再重命名，其他读取者不会读到写了一半的内容
+    fn write(&self, id: &str, content: &[u8]) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut file = tempfile::NamedTempFile::new_in(&self.dir)?;
+        file.write_all(content)?;
+        file.persist(self.path(id))?;
+        Ok(())
+    }
+}
+
+impl InMemStore for FileStore {
+    fn get(&self, id: &str) -> Option<ApiResponse> {
+        self.read(id).map(ApiResponse::new)
     }
 
     fn insert(
@@ -40,6 +96,85 @@ impl InMemStore for Store {
         val: ApiResponse,
         lifetime: Option<time::Duration>,
     ) -> Option<ApiResponse> {
-        self.0.write().unwrap().insert(id, val, lifetime)
+        let old = self.get(&id);
+        // 不足一秒的有效期向上取整，避免写入后立即过期
+        let expire = lifetime
+            .map(|l| now_secs() + l.as_secs_f64().ceil() as u64)
+            .unwrap_or(0);
+        let mut content = format!("{}\n", expire).into_bytes();
+        content.extend_from_slice(val.data());
+        // 缓存只是优化，写入失败时忽略
+        self.write(&id, &content).ok();
+        old
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_file_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().join("http_cache"));
+        assert!(store.get("id").is_none());
+
+        let old = store.insert(
+            "id".to_owned(),
+            ApiResponse::new(b"{\"code\":200}".to_vec()),
+            None,
+        );
+        assert!(old.is_none());
+        assert!(store.get("id").is_some());
+        assert_eq!(store.get("id").unwrap().data(), b"{\"code\":200}");
+
+        // 重新打开缓存目录仍能读到
+        let store = FileStore::new(dir.path().join("http_cache"));
+        assert!(store.get("id").is_some());
+    }
+
+    #[test]
+    fn test_file_store_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().to_path_buf());
+        store.insert(
+            "id".to_owned(),
+            ApiResponse::new(b"{}".to_vec()),
+            Some(Duration::from_secs(0)),
+        );
+        assert!(store.get("id").is_none());
+        assert!(!dir.path().join("id").exists());
+
+        // 不足一秒的有效期不会立即过期
+        store.insert(
+            "id".to_owned(),
+            ApiResponse::new(b"{}".to_vec()),
+            Some(Duration::from_millis(500)),
+        );
+        assert!(store.get("id").is_some());
+    }
+
+    #[test]
+    fn test_file_store_write_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        // 缓存目录的位置已被文件占用，无法写入
+        let path = dir.path().join("http_cache");
+        fs::write(&path, b"").unwrap();
+        let store = FileStore::new(path);
+        store.insert("id".to_owned(), ApiResponse::new(b"{}".to_vec()), None);
+        assert!(store.get("id").is_none());
+        // 写入完成后不留下临时文件
+        let store = FileStore::new(dir.path().join("cache"));
+        store.insert("id".to_owned(), ApiResponse::new(b"{}".to_vec()), None);
+        assert_eq!(fs::read_dir(dir.path().join("cache")).unwrap().count(), 1);
     }
 }
@@ -0,0 +1,199 @@
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::{Client, Request, Response};
+use serde_json::Value;
+
+/// 交给传输层的请求，除了加密后的http请求外，还带有加密前的接口地址和参数，
+/// 方便在不访问网络时根据接口和参数返回对应的响应
+pub struct TransportRequest {
+    // 接口地址，即API_ROUTE中的地址
+    pub route: String,
+    // 加密前的请求参数
+    pub params: Value,
+    pub request: Request,
+}
+
+#[async_trait]
+pub trait Transport: Debug + Send + Sync {
+    async fn execute(&self, req: TransportRequest) -> Result<Response>;
+}
+
+/// 通过网络发送请求
+#[derive(Debug)]
+pub struct HttpTransport {
+    client: Client,
+}
+
+impl HttpTransport {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn execute(&self, req: TransportRequest) -> Result<Response> {
+        Ok(self.client.execute(req.request).await?)
+    }
+}
+
+/// 返回预设的响应，不访问网络，用于测试
+#[derive(Debug, Default)]
+pub struct FixtureTransport {
+    fixtures: Vec<Fixture>,
+    // 已收到的请求数
+    requests: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct Fixture {
+    route: String,
+    params: Option<Value>,
+    body: Vec<u8>,
+}
+
+#[allow(unused)]
+impl FixtureTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求该接口时返回body，不论请求参数
+    pub fn route(self, route: &str, body: Value) -> Self {
+        self.add(route, None, body.to_string().into_bytes())
+    }
+
+    /// 请求该接口且请求参数包含params中所有字段时返回body，优先于不带参数的响应
+    pub fn route_with_params(self, route: &str, params: Value, body: Value) -> Self {
+        self.add(route, Some(params), body.to_string().into_bytes())
+    }
+
+    /// 请求该接口时原样返回body，用于模拟加密的响应
+    pub fn route_raw(self, route: &str, body: Vec<u8>) -> Self {
+        self.add(route, None, body)
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    fn add(mut self, route: &str, params: Option<Value>, body: Vec<u8>) -> Self {
+        self.fixtures.push(Fixture {
+            route: route.to_owned(),
+            params,
+            body,
+        });
+        self
+    }
+
+    fn find(&self, route: &str, params: &Value) -> Option<&[u8]> {
+        let candidates = self.fixtures.iter().filter(|f| f.route == route);
+        candidates
+            .clone()
+            .find(|f| f.params.as_ref().is_some_and(|p| contains(params, p)))
+            .or_else(|| candidates.clone().find(|f| f.params.is_none()))
+            .map(|f| f.body.as_slice())
+    }
+}
+
+#[async_trait]
+impl Transport for FixtureTransport {
+    async fn execute(&self, req: TransportRequest) -> Result<Response> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let body = self
+            .find(&req.route, &req.params)
+            .ok_or_else(|| anyhow!("no fixture for {} {}", req.route, req.params))?;
+        let resp = http::Response::builder().status(200).body(body.to_vec())?;
+        Ok(Response::from(resp))
+    }
+}
+
+/// 接口返回的歌曲，用于测试
+#[cfg(test)]
+pub fn track_json(id: usize, name: &str) -> Value {
+    serde_json::json!({
+        "id": id,
+        "name": name,
+        "ar": [{ "id": 12279635, "name": "Mr.mo" }],
+        "al": { "id": 32311, "name": "再来一杯" },
+        "dt": 330000,
+        "fee": 0,
+        "pop": 100.0
+    })
+}
+
+/// 带有临时目录的测试对象，cookie和缓存写入该目录，对象释放时删除目录
+#[cfg(test)]
+pub struct WithTempDir<T> {
+    inner: T,
+    dir: tempfile::TempDir,
+}
+
+#[cfg(test)]
+impl<T> WithTempDir<T> {
+    pub fn new(build: impl FnOnce(&std::path::Path) -> T) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = build(dir.path());
+        Self { inner, dir }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WithTempDir<U> {
+        WithTempDir {
+            inner: f(self.inner),
+            dir: self.dir,
+        }
+    }
+}
+
+#[cfg(test)]
+impl<T> std::ops::Deref for WithTempDir<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+// params是否包含expected中的所有字段
+fn contains(params: &Value, expected: &Value) -> bool {
+    match (params, expected) {
+        (Value::Object(params), Value::Object(expected)) => expected
+            .iter()
+            .all(|(k, v)| params.get(k).is_some_and(|p| contains(p, v))),
+        _ => params == expected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{contains, FixtureTransport};
+
+    #[test]
+    fn test_contains() {
+        let params = json!({ "s": "mota", "type": 1, "limit": 30 });
+        assert!(contains(&params, &json!({ "s": "mota" })));
+        assert!(!contains(&params, &json!({ "s": "other" })));
+        assert!(!contains(&params, &json!({ "offset": 0 })));
+    }
+
+    #[test]
+    fn test_find_fixture() {
+        let transport = FixtureTransport::new()
+            .route("/search", json!({ "code": 200 }))
+            .route_with_params("/search", json!({ "s": "mota" }), json!({ "code": 400 }));
+        assert_eq!(
+            transport.find("/search", &json!({ "s": "mota" })),
+            Some(json!({ "code": 400 }).to_string().as_bytes())
+        );
+        assert_eq!(
+            transport.find("/search", &json!({ "s": "other" })),
+            Some(json!({ "code": 200 }).to_string().as_bytes())
+        );
+        assert_eq!(transport.find("/other", &json!({})), None);
+    }
+}
@@ -9,6 +9,8 @@ use crate::app::App;
 use crate::cli::clap::ClapApplication;
 use crate::config::user_config::{UserConfig, UserConfigPath};
 use crate::event::IoEvent;
+use crate::http::cassette::{self, CassetteMode};
+use crate::network::cloud_music::CloudMusic;
 use crate::network::{panic_hook, start_tokio, Network};
 
 // mod api;
@@ -44,6 +46,16 @@ async fn main() -> Result<()> {
         user_config.path_to_config.replace(config_path);
     }
     user_config.load_config()?;
+    config::network::init(user_config.network.clone());
+    cassette::init(CassetteMode::from_args(
+        matches.value_of("record"),
+        matches.value_of("replay"),
+        matches.is_present("replay-fallback"),
+    ));
+    if let Some(cookies) = matches.value_of("cookie") {
+        let profile = CloudMusic::default().login_cookies(cookies).await?;
+        println!("{} 登录成功", profile.nickname);
+    }
     // login_phone().await?;
 
     let (sync_io_tx, sync_io_rx) = mpsc::channel::<IoEvent>();
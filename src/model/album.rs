@@ -1,3 +1,4 @@
+use crate::http::response::ApiResp;
 use crate::model::artist::Artist;
 use crate::model::table::TableItem;
 use crate::model::track::Track;
@@ -19,6 +20,10 @@ pub struct AlbumResp {
     pub album: Album,
 }
 
+impl ApiResp for AlbumResp {
+    const ERROR: &'static str = "获取专辑失败";
+}
+
 #[derive(Clone)]
 pub struct AlbumDetail {
     pub album: Album,
@@ -1,3 +1,4 @@
+use crate::http::response::ApiResp;
 use crate::model::album::Album;
 use crate::model::track::Track;
 
@@ -18,6 +19,10 @@ pub struct ArtistSublistResp {
     pub has_more: bool,
 }
 
+impl ApiResp for ArtistSublistResp {
+    const ERROR: &'static str = "获取关注歌手失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ArtistTracksResp {
     pub code: usize,
@@ -25,12 +30,20 @@ pub struct ArtistTracksResp {
     pub tracks: Vec<Track>,
 }
 
+impl ApiResp for ArtistTracksResp {
+    const ERROR: &'static str = "获取歌曲失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SimiArtistsResp {
     pub code: usize,
     pub artists: Vec<Artist>,
 }
 
+impl ApiResp for SimiArtistsResp {
+    const ERROR: &'static str = "获取相似歌手失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ArtistAlbumResp {
@@ -40,6 +53,10 @@ pub struct ArtistAlbumResp {
     pub hot_albums: Vec<Album>,
 }
 
+impl ApiResp for ArtistAlbumResp {
+    const ERROR: &'static str = "获取歌手专辑失败";
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum ArtistBlock {
     Tracks,
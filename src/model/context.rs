@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::model::enums::{CurrentlyPlayingType, RepeatState};
 use crate::model::track::Track;
@@ -12,11 +12,23 @@ pub struct CurrentlyPlaybackContext {
     // 当前播放的是什么
     pub currently_playing_type: CurrentlyPlayingType,
     // 重复状态
+    #[serde(deserialize_with = "deserialize_repeat_state")]
     pub repeat_state: RepeatState,
     // 当前播放项
     pub item: Option<Track>,
 }
 
+// 心动模式的歌曲只保存在内存中，恢复时改为随机播放
+fn deserialize_repeat_state<'de, D>(deserializer: D) -> Result<RepeatState, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match RepeatState::deserialize(deserializer)? {
+        RepeatState::Intelligence => Ok(RepeatState::Shuffle),
+        state => Ok(state),
+    }
+}
+
 impl CurrentlyPlaybackContext {
     pub fn new(item: Option<Track>) -> Self {
         CurrentlyPlaybackContext {
@@ -55,6 +67,8 @@ pub enum DialogContext {
     SubPlaylist,
     #[allow(unused)]
     PlaylistSearch,
+    Logout,
+    RemoveAccount,
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -73,4 +87,29 @@ pub enum TrackTableContext {
     SearchResult,
     ArtistDetail,
     AlbumDetail,
+    // 私人FM
+    PersonalFm,
+    // 心动模式
+    Intelligence,
+    // 相似歌曲电台
+    Radio,
+    // 排行榜
+    Toplist,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CurrentlyPlaybackContext;
+    use crate::model::enums::RepeatState;
+
+    #[test]
+    fn test_restore_intelligence_as_shuffle() {
+        let context = CurrentlyPlaybackContext {
+            repeat_state: RepeatState::Intelligence,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&context).unwrap();
+        let context = serde_json::from_str::<CurrentlyPlaybackContext>(&json).unwrap();
+        assert_eq!(context.repeat_state, RepeatState::Shuffle);
+    }
 }
@@ -0,0 +1,270 @@
+use crate::http::response::ApiResp;
+use crate::model::playlist::Playlist;
+
+// 每页歌单数
+pub const DISCOVER_PAGE_SIZE: usize = 50;
+// 选中位置距已加载歌单的末尾少于该数量时加载下一页
+const DISCOVER_PREFETCH_THRESHOLD: usize = 10;
+// 不限分类
+pub const ALL_CATEGORY: &str = "全部";
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PlaylistCategory {
+    pub name: String,
+    // 所属的大类，如语种、风格、场景
+    #[serde(default)]
+    pub category: usize,
+    #[serde(default)]
+    pub hot: bool,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PlaylistCatlistResp {
+    pub code: usize,
+    #[serde(default)]
+    pub sub: Vec<PlaylistCategory>,
+}
+
+impl ApiResp for PlaylistCatlistResp {
+    const ERROR: &'static str = "获取歌单分类失败";
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HighqualityTagsResp {
+    pub code: usize,
+    #[serde(default)]
+    pub tags: Vec<PlaylistCategory>,
+}
+
+impl ApiResp for HighqualityTagsResp {
+    const ERROR: &'static str = "获取精品歌单标签失败";
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TopPlaylistResp {
+    pub code: usize,
+    #[serde(default)]
+    pub playlists: Vec<Playlist>,
+    #[serde(default)]
+    pub total: usize,
+    #[serde(default)]
+    pub more: bool,
+    // 精品歌单分页用，本页最后一个歌单的更新时间
+    #[serde(default)]
+    pub lasttime: u64,
+}
+
+impl ApiResp for TopPlaylistResp {
+    const ERROR: &'static str = "获取歌单失败";
+}
+
+// 歌单排序，精品歌单只能按精品标签分类
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaylistOrder {
+    // 最热
+    Hot,
+    // 最新
+    New,
+    // 精品
+    HighQuality,
+}
+
+impl PlaylistOrder {
+    pub fn next(self) -> Self {
+        match self {
+            PlaylistOrder::Hot => PlaylistOrder::New,
+            PlaylistOrder::New => PlaylistOrder::HighQuality,
+            PlaylistOrder::HighQuality => PlaylistOrder::Hot,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PlaylistOrder::Hot => "最热",
+            PlaylistOrder::New => "最新",
+            PlaylistOrder::HighQuality => "精品",
+        }
+    }
+}
+
+// 下一页歌单的请求参数(分类, 排序, 偏移, 精品歌单的起始时间)
+pub type DiscoverPage = (String, PlaylistOrder, usize, u64);
+
+// 按分类发现歌单
+#[derive(Clone)]
+pub struct Discover {
+    // 歌单分类，第一个为全部
+    pub categories: Vec<String>,
+    // 精品歌单标签，第一个为全部
+    pub highquality_tags: Vec<String>,
+    pub category_index: usize,
+    pub order: PlaylistOrder,
+    pub playlists: Vec<Playlist>,
+    pub selected_index: usize,
+    // 当前分类的歌单总数
+    pub total: usize,
+    pub more: bool,
+    pub lasttime: u64,
+    // 正在获取歌单
+    pub is_fetching: bool,
+}
+
+impl Default for Discover {
+    fn default() -> Self {
+        Discover {
+            categories: vec![ALL_CATEGORY.to_string()],
+            highquality_tags: vec![ALL_CATEGORY.to_string()],
+            category_index: 0,
+            order: PlaylistOrder::Hot,
+            playlists: vec![],
+            selected_index: 0,
+            total: 0,
+            more: false,
+            lasttime: 0,
+            is_fetching: false,
+        }
+    }
+}
+
+impl Discover {
+    /// 当前排序可选的分类
+    pub fn current_categories(&self) -> &[String] {
+        match self.order {
+            PlaylistOrder::HighQuality => &self.highquality_tags,
+            _ => &self.categories,
+        }
+    }
+
+    pub fn current_category(&self) -> String {
+        self.current_categories()
+            .get(self.category_index)
+            .cloned()
+            .unwrap_or_else(|| ALL_CATEGORY.to_string())
+    }
+
+    pub fn set_categories(&mut self, categories: Vec<String>, highquality_tags: Vec<String>) {
+        let category = self.current_category();
+        let with_all = |names: Vec<String>| {
+            std::iter::once(ALL_CATEGORY.to_string())
+                .chain(names.into_iter().filter(|name| name != ALL_CATEGORY))
+                .collect::<Vec<String>>()
+        };
+        self.categories = with_all(categories);
+        self.highquality_tags = with_all(highquality_tags);
+        self.select_category(&category);
+    }
+
+    /// 切换排序，新的排序没有当前分类时选中全部
+    pub fn toggle_order(&mut self) {
+        let category = self.current_category();
+        self.order = self.order.next();
+        self.select_category(&category);
+    }
+
+    /// 清空歌单，返回第一页的请求参数
+    pub fn reset(&mut self) -> DiscoverPage {
+        self.playlists.clear();
+        self.selected_index = 0;
+        self.total = 0;
+        self.more = false;
+        self.lasttime = 0;
+        self.is_fetching = true;
+        (self.current_category(), self.order, 0, 0)
+    }
+
+    /// 添加获取到的一页歌单，分类或排序已切换时丢弃
+    pub fn append_page(&mut self, category: &str, order: PlaylistOrder, resp: TopPlaylistResp) {
+        if category != self.current_category() || order != self.order {
+            return;
+        }
+        self.is_fetching = false;
+        for playlist in resp.playlists {
+            if !self.playlists.iter().any(|p| p.id == playlist.id) {
+                self.playlists.push(playlist);
+            }
+        }
+        self.total = resp.total.max(self.playlists.len());
+        self.more = resp.more;
+        self.lasttime = resp.lasttime;
+    }
+
+    /// 选中位置接近已加载歌单的末尾且还有更多歌单时，返回下一页的请求参数
+    pub fn next_page(&self, index: usize) -> Option<DiscoverPage> {
+        if self.is_fetching
+            || !self.more
+            || index + DISCOVER_PREFETCH_THRESHOLD < self.playlists.len()
+        {
+            return None;
+        }
+        Some((
+            self.current_category(),
+            self.order,
+            self.playlists.len(),
+            self.lasttime,
+        ))
+    }
+
+    fn select_category(&mut self, category: &str) {
+        self.category_index = self
+            .current_categories()
+            .iter()
+            .position(|name| name == category)
+            .unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Discover, PlaylistOrder, TopPlaylistResp, ALL_CATEGORY};
+    use crate::model::playlist::Playlist;
+
+    fn page(ids: &[usize], more: bool) -> TopPlaylistResp {
+        TopPlaylistResp {
+            playlists: ids
+                .iter()
+                .map(|&id| Playlist {
+                    id,
+                    ..Default::default()
+                })
+                .collect(),
+            total: 100,
+            more,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_toggle_order() {
+        let mut discover = Discover::default();
+        discover.set_categories(
+            vec!["华语".to_string(), "摇滚".to_string()],
+            vec!["华语".to_string()],
+        );
+        assert_eq!(discover.categories, vec![ALL_CATEGORY, "华语", "摇滚"]);
+        discover.category_index = 2;
+        discover.toggle_order();
+        assert_eq!(discover.order, PlaylistOrder::New);
+        assert_eq!(discover.current_category(), "摇滚");
+        // 精品标签中没有该分类
+        discover.toggle_order();
+        assert_eq!(discover.current_category(), ALL_CATEGORY);
+    }
+
+    #[test]
+    fn test_paging() {
+        let mut discover = Discover::default();
+        let (category, order, offset, _) = discover.reset();
+        assert_eq!(offset, 0);
+        assert_eq!(discover.next_page(0), None);
+        discover.append_page(&category, order, page(&[1, 2, 3], true));
+        assert_eq!(discover.next_page(0), Some((category.clone(), order, 3, 0)));
+        // 切换排序后丢弃之前的请求结果
+        discover.toggle_order();
+        discover.reset();
+        discover.append_page(&category, order, page(&[4], false));
+        assert!(discover.playlists.is_empty());
+        discover.append_page(&category, discover.order, page(&[4], false));
+        assert_eq!(discover.playlists.len(), 1);
+        assert_eq!(discover.next_page(0), None);
+    }
+}
@@ -55,6 +55,8 @@ pub enum RepeatState {
     Context,
     // 随机
     Shuffle,
+    // 心动模式，播放喜欢的歌曲和推荐的相似歌曲
+    Intelligence,
 }
 
 // 当前播放的类型
@@ -0,0 +1,94 @@
+use crate::http::response::ApiResp;
+use crate::model::track::Track;
+
+// 待播放的歌曲少于该数量时获取更多
+const FM_REFILL_THRESHOLD: usize = 2;
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PersonalFmResp {
+    pub code: usize,
+    #[serde(default)]
+    pub data: Vec<Track>,
+}
+
+impl ApiResp for PersonalFmResp {
+    const ERROR: &'static str = "获取私人FM失败";
+}
+
+// 私人FM，tracks中index之前的是已播放的歌曲，之后的是待播放的歌曲
+#[derive(Default, Clone)]
+pub struct PersonalFm {
+    pub tracks: Vec<Track>,
+    pub index: usize,
+    // 正在获取更多歌曲
+    pub is_fetching: bool,
+    // 获取到歌曲后立即播放当前歌曲，用于首次进入FM或待播放的歌曲已用完
+    pub play_when_loaded: bool,
+}
+
+impl PersonalFm {
+    pub fn current(&self) -> Option<&Track> {
+        self.tracks.get(self.index)
+    }
+
+    pub fn upcoming(&self) -> &[Track] {
+        self.tracks.get(self.index + 1..).unwrap_or_default()
+    }
+
+    pub fn needs_refill(&self) -> bool {
+        !self.is_fetching && self.upcoming().len() < FM_REFILL_THRESHOLD
+    }
+
+    /// 添加获取到的歌曲，跳过已有的歌曲
+    pub fn extend(&mut self, tracks: Vec<Track>) {
+        for track in tracks {
+            if !self.tracks.iter().any(|t| t.id == track.id) {
+                self.tracks.push(track);
+            }
+        }
+    }
+
+    /// 切换到下一首，没有待播放的歌曲时停在末尾，获取到更多歌曲后即为当前歌曲
+    pub fn next(&mut self) -> Option<Track> {
+        self.index = (self.index + 1).min(self.tracks.len());
+        self.current().cloned()
+    }
+
+    pub fn prev(&mut self) -> Option<Track> {
+        if self.index == 0 {
+            return None;
+        }
+        self.index -= 1;
+        self.current().cloned()
+    }
+
+    /// 移除不喜欢的当前歌曲，返回之后的歌曲
+    pub fn trash_current(&mut self) -> Option<Track> {
+        if self.index < self.tracks.len() {
+            self.tracks.remove(self.index);
+        }
+        self.current().cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PersonalFm;
+    use crate::model::track::track;
+
+    #[test]
+    fn test_personal_fm() {
+        let mut fm = PersonalFm::default();
+        assert!(fm.needs_refill());
+        fm.extend(vec![track(1), track(2), track(2)]);
+        assert_eq!(fm.tracks.len(), 2);
+        assert_eq!(fm.next(), Some(track(2)));
+        // 待播放的歌曲用完后停在末尾，获取到的歌曲即为当前歌曲
+        assert_eq!(fm.next(), None);
+        fm.extend(vec![track(3), track(4)]);
+        assert_eq!(fm.current(), Some(&track(3)));
+        assert_eq!(fm.trash_current(), Some(track(4)));
+        assert_eq!(fm.prev(), Some(track(2)));
+        assert_eq!(fm.upcoming(), &[track(4)]);
+    }
+}
@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+use crate::http::response::ApiResp;
+use crate::model::table::TrackTable;
+use crate::model::track::Track;
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IntelligenceResp {
+    pub code: usize,
+    #[serde(default)]
+    pub data: Vec<IntelligenceItem>,
+}
+
+impl ApiResp for IntelligenceResp {
+    const ERROR: &'static str = "获取心动模式歌曲失败";
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntelligenceItem {
+    pub id: usize,
+    // 是否是推荐的歌曲，false为喜欢的歌曲
+    #[serde(default)]
+    pub recommended: bool,
+    pub song_info: Option<Track>,
+}
+
+// 心动模式，播放列表为喜欢的歌曲和推荐的相似歌曲
+#[derive(Default, Clone)]
+pub struct Intelligence {
+    // 我喜欢歌单的id
+    pub playlist_id: usize,
+    // 最近一次请求的种子歌曲，返回的结果与之不符时丢弃
+    pub seed_id: usize,
+    // 推荐的歌曲，在列表中标记出来
+    pub recommended_ids: HashSet<usize>,
+    // 开启心动模式前的播放列表，退出后恢复
+    pub origin: Option<TrackTable>,
+    // 正在获取更多歌曲
+    pub is_fetching: bool,
+}
@@ -1,22 +1,83 @@
+use std::time::{Duration, Instant};
+
+use crate::http::response::ApiResp;
 use crate::model::user::UserProfile;
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct LoginInfo {
     pub phone: String,
+    pub email: String,
+    // 导入的cookie，或cookies.txt文件路径
+    pub cookie: String,
     pub password: String,
     pub login_state: LoginState,
     pub cancel_login: bool,
     pub is_login_success: bool,
+    pub login_mode: LoginMode,
+    // 国家码，如中国为86
+    pub country_code: String,
+    // 可选的国家/地区列表
+    pub countries: Vec<Country>,
+    // 搜索国家/地区的关键字
+    pub country_search: String,
+    pub country_selected_index: usize,
+    // 短信验证码
+    pub captcha: String,
+    // 上次发送验证码的时间
+    pub captcha_sent_time: Option<Instant>,
+    // 扫码登录
+    pub qr_login: Option<QrLogin>,
 }
 
 impl Default for LoginInfo {
     fn default() -> Self {
         Self {
             phone: "".to_string(),
+            email: "".to_string(),
+            cookie: "".to_string(),
             password: "".to_string(),
             login_state: Default::default(),
             cancel_login: false,
             is_login_success: false,
+            login_mode: Default::default(),
+            country_code: DEFAULT_COUNTRY_CODE.to_string(),
+            countries: vec![],
+            country_search: "".to_string(),
+            country_selected_index: 0,
+            captcha: "".to_string(),
+            captcha_sent_time: None,
+            qr_login: None,
+        }
+    }
+}
+
+pub const DEFAULT_COUNTRY_CODE: &str = "86";
+
+// 重新发送验证码的间隔
+pub const CAPTCHA_COOLDOWN: Duration = Duration::from_secs(60);
+
+impl LoginInfo {
+    // 按关键字过滤国家/地区，匹配中文名、英文名或国家码
+    pub fn filtered_countries(&self) -> Vec<&Country> {
+        let keyword = self.country_search.to_lowercase();
+        self.countries
+            .iter()
+            .filter(|c| {
+                keyword.is_empty()
+                    || c.zh.contains(&keyword)
+                    || c.en.to_lowercase().contains(&keyword)
+                    || c.code.starts_with(&keyword)
+            })
+            .collect()
+    }
+
+    // 距离可以重新发送验证码的剩余秒数
+    pub fn captcha_cooldown_remaining(&self) -> u64 {
+        match self.captcha_sent_time {
+            Some(time) => CAPTCHA_COOLDOWN
+                .checked_sub(time.elapsed())
+                .map_or(0, |d| d.as_secs() + 1),
+            None => 0,
         }
     }
 }
@@ -24,8 +85,15 @@ impl Default for LoginInfo {
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LoginResp {
-    pub code: usize,
+    // 失败时可能为负数，如-460
+    pub code: i64,
     pub profile: Option<UserProfile>,
+    #[serde(default)]
+    pub msg: Option<String>,
+}
+
+impl ApiResp for LoginResp {
+    const ERROR: &'static str = "登录失败……";
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -43,6 +111,162 @@ impl Default for LoginState {
 
 #[derive(Clone, Debug)]
 pub struct LoginForm {
+    pub country_code: String,
     pub phone: String,
     pub password: String,
 }
+
+#[derive(Clone, Debug)]
+pub struct EmailLoginForm {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct CaptchaLoginForm {
+    pub country_code: String,
+    pub phone: String,
+    pub captcha: String,
+}
+
+// 登录方式
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum LoginMode {
+    #[default]
+    Phone,
+    Sms,
+    Email,
+    QrCode,
+    Cookie,
+}
+
+impl LoginMode {
+    pub const ALL: [LoginMode; 5] = [
+        LoginMode::Phone,
+        LoginMode::Sms,
+        LoginMode::Email,
+        LoginMode::QrCode,
+        LoginMode::Cookie,
+    ];
+
+    pub fn next(&self) -> Self {
+        match self {
+            LoginMode::Phone => LoginMode::Sms,
+            LoginMode::Sms => LoginMode::Email,
+            LoginMode::Email => LoginMode::QrCode,
+            LoginMode::QrCode => LoginMode::Cookie,
+            LoginMode::Cookie => LoginMode::Phone,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            LoginMode::Phone => "手机号登录",
+            LoginMode::Sms => "验证码登录",
+            LoginMode::Email => "邮箱登录",
+            LoginMode::QrCode => "扫码登录",
+            LoginMode::Cookie => "Cookie登录",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct QrLogin {
+    pub key: String,
+    // 渲染好的二维码字符画
+    pub image: String,
+    pub state: QrLoginState,
+    // 上次查询扫码状态的时间
+    pub last_check: Instant,
+}
+
+impl QrLogin {
+    pub fn new(key: String, image: String) -> Self {
+        Self {
+            key,
+            image,
+            state: QrLoginState::Waiting,
+            last_check: Instant::now(),
+        }
+    }
+}
+
+// 扫码状态，对应login_qr_check返回的800~803
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum QrLoginState {
+    Expired,
+    Waiting,
+    Scanned,
+    Confirmed,
+    // 查询扫码状态失败，停止查询直到用户刷新二维码
+    Failed,
+}
+
+impl QrLoginState {
+    pub fn from_code(code: isize) -> Option<Self> {
+        match code {
+            800 => Some(QrLoginState::Expired),
+            801 => Some(QrLoginState::Waiting),
+            802 => Some(QrLoginState::Scanned),
+            803 => Some(QrLoginState::Confirmed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct QrKeyResp {
+    pub code: usize,
+    #[serde(default)]
+    pub unikey: String,
+}
+
+impl ApiResp for QrKeyResp {
+    const ERROR: &'static str = "获取登录二维码失败……";
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct QrCheckResp {
+    pub code: isize,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CaptchaResp {
+    pub code: usize,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl ApiResp for CaptchaResp {
+    const ERROR: &'static str = "验证码错误";
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Country {
+    pub zh: String,
+    pub en: String,
+    pub locale: String,
+    pub code: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryGroup {
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub country_list: Vec<Country>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CountriesResp {
+    pub code: usize,
+    #[serde(default)]
+    pub data: Vec<CountryGroup>,
+}
+
+impl ApiResp for CountriesResp {
+    const ERROR: &'static str = "获取国家/地区列表失败……";
+}
@@ -3,13 +3,18 @@ pub(crate) mod artist;
 pub(crate) mod context;
 pub(crate) mod device;
 pub(crate) mod dialog;
+pub(crate) mod discover;
 pub(crate) mod enums;
+pub(crate) mod fm;
 pub(crate) mod image;
+pub(crate) mod intelligence;
 pub(crate) mod login;
 pub(crate) mod page;
 pub(crate) mod playlist;
+pub(crate) mod radio;
 pub(crate) mod show;
 pub(crate) mod table;
+pub(crate) mod toplist;
 pub(crate) mod track;
 pub(crate) mod user;
 
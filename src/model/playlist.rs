@@ -1,4 +1,6 @@
+use crate::http::response::ApiResp;
 use crate::model::track::Track;
+use crate::model::user::UserProfile;
 use crate::model::Id;
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -8,7 +10,15 @@ pub struct Playlist {
     pub name: String,
     pub description: Option<String>,
     // 是否是收藏歌单，true/是，false/否
+    #[serde(default)]
     pub subscribed: bool,
+    // 创建者、播放数和歌曲数，发现歌单中显示
+    #[serde(default)]
+    pub creator: Option<UserProfile>,
+    #[serde(default)]
+    pub play_count: u64,
+    #[serde(default)]
+    pub track_count: usize,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -18,6 +28,10 @@ pub struct UserPlaylistResp {
     pub playlist: Vec<Playlist>,
 }
 
+impl ApiResp for UserPlaylistResp {
+    const ERROR: &'static str = "歌单获取失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaylistDetail {
@@ -37,9 +51,17 @@ pub struct PlaylistDetailResp {
     pub playlist: Option<PlaylistDetail>,
 }
 
+impl ApiResp for PlaylistDetailResp {
+    const ERROR: &'static str = "获取歌单歌曲失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct PlaylistTracksResp {
     pub code: usize,
     #[serde(rename = "songs")]
     pub tracks: Vec<Track>,
 }
+
+impl ApiResp for PlaylistTracksResp {
+    const ERROR: &'static str = "获取歌单歌曲失败";
+}
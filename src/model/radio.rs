@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+
+use crate::http::response::ApiResp;
+use crate::model::track::Track;
+
+// 待播放的歌曲少于该数量时获取更多相似歌曲
+const RADIO_REFILL_THRESHOLD: usize = 5;
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SimiSongResp {
+    pub code: usize,
+    #[serde(default)]
+    pub songs: Vec<Track>,
+}
+
+impl ApiResp for SimiSongResp {
+    const ERROR: &'static str = "获取相似歌曲失败";
+}
+
+// 相似歌曲电台，以一首歌曲开始，播放过程中不断加入已播放歌曲的相似歌曲
+#[derive(Default, Clone)]
+pub struct Radio {
+    // 已获取过相似歌曲的歌曲
+    pub seeded_ids: HashSet<usize>,
+    // 最近一次请求的歌曲，返回的结果与之不符时丢弃
+    pub seed_id: usize,
+    // 正在获取相似歌曲
+    pub is_fetching: bool,
+}
+
+impl Radio {
+    pub fn new(seed_id: usize) -> Self {
+        Radio {
+            seeded_ids: HashSet::from([seed_id]),
+            seed_id,
+            is_fetching: true,
+        }
+    }
+
+    /// 播放到index时需要获取相似歌曲的歌曲id，优先使用最近播放过的歌曲，
+    /// 待播放的歌曲足够或正在获取时返回None
+    pub fn next_seed(&self, tracks: &[Track], index: usize) -> Option<usize> {
+        if self.is_fetching || index + RADIO_REFILL_THRESHOLD < tracks.len() {
+            return None;
+        }
+        let played = tracks.iter().take(index + 1).rev();
+        let upcoming = tracks.iter().skip(index + 1);
+        played
+            .chain(upcoming)
+            .map(|track| track.id)
+            .find(|id| !self.seeded_ids.contains(id))
+    }
+}
+
+/// 过滤已在播放列表中和不喜欢的歌曲
+pub fn filter_new_tracks(
+    tracks: Vec<Track>,
+    queue: &[Track],
+    disliked: &HashSet<usize>,
+) -> Vec<Track> {
+    let mut ids = queue.iter().map(|t| t.id).collect::<HashSet<_>>();
+    tracks
+        .into_iter()
+        .filter(|t| !disliked.contains(&t.id) && ids.insert(t.id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{filter_new_tracks, Radio};
+    use crate::model::track::track;
+
+    #[test]
+    fn test_filter_new_tracks() {
+        let tracks = filter_new_tracks(
+            vec![track(1), track(2), track(3), track(3), track(4)],
+            &[track(1)],
+            &HashSet::from([2]),
+        );
+        assert_eq!(tracks, vec![track(3), track(4)]);
+    }
+
+    #[test]
+    fn test_next_seed() {
+        let mut radio = Radio::new(1);
+        let tracks = (1..=8).map(track).collect::<Vec<_>>();
+        // 正在获取时不再获取
+        assert_eq!(radio.next_seed(&tracks, 5), None);
+        radio.is_fetching = false;
+        // 待播放的歌曲足够
+        assert_eq!(radio.next_seed(&tracks, 1), None);
+        assert_eq!(radio.next_seed(&tracks, 3), Some(4));
+        radio.seeded_ids.extend([2, 3, 4]);
+        // 已播放的歌曲都获取过时使用待播放的歌曲
+        assert_eq!(radio.next_seed(&tracks, 3), Some(5));
+    }
+}
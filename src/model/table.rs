@@ -1,12 +1,107 @@
+use std::collections::HashSet;
+
+use crate::http::response::ApiResp;
 #[allow(unused)]
 use crate::model::context::TrackTableContext;
 use crate::model::track::Track;
 
+// 歌单每页加载的歌曲数
+pub const PLAYLIST_PAGE_SIZE: usize = 100;
+
 #[derive(Default, Clone)]
 pub struct TrackTable {
     pub tracks: Vec<Track>,
     pub selected_index: usize,
     pub context: Option<TrackTableContext>,
+    // 按需分页加载的歌单，此时tracks只包含已加载的歌曲
+    pub pagination: Option<TrackPagination>,
+}
+
+#[derive(Default, Clone)]
+pub struct TrackPagination {
+    pub playlist_id: usize,
+    // 歌单中全部歌曲的id
+    pub track_ids: Vec<usize>,
+    // track_ids中已加载的位置，接口可能不返回已下架的歌曲，因此可能多于tracks的数量
+    pub loaded: usize,
+    // 已请求加载到的位置，避免重复请求同一页
+    pub requested: usize,
+}
+
+impl TrackTable {
+    // 歌曲总数，分页加载时包含还未加载的歌曲
+    pub fn total(&self) -> usize {
+        match &self.pagination {
+            Some(pagination) => pagination.track_ids.len(),
+            None => self.tracks.len(),
+        }
+    }
+
+    /// 位置index接近已加载歌曲的末尾时，返回需要预加载的下一页(歌单id, 起始位置, 歌曲id)
+    pub fn next_page(&mut self, index: usize) -> Option<(usize, usize, Vec<usize>)> {
+        let pagination = self.pagination.as_mut()?;
+        // 已请求但还未返回的页也算作已加载，只保持预加载一页
+        let start = pagination.requested.max(pagination.loaded);
+        if index + PLAYLIST_PAGE_SIZE < start || start >= pagination.track_ids.len() {
+            return None;
+        }
+        let end = (start + PLAYLIST_PAGE_SIZE).min(pagination.track_ids.len());
+        pagination.requested = end;
+        Some((
+            pagination.playlist_id,
+            start,
+            pagination.track_ids[start..end].to_vec(),
+        ))
+    }
+
+    /// 跳转到末尾或随机播放时需要加载剩余的全部歌曲，已全部请求过时返回None
+    pub fn remaining_pages(&mut self) -> Option<(usize, usize, Vec<usize>)> {
+        let pagination = self.pagination.as_mut()?;
+        let start = pagination.loaded;
+        if start >= pagination.track_ids.len() || pagination.requested >= pagination.track_ids.len()
+        {
+            return None;
+        }
+        pagination.requested = pagination.track_ids.len();
+        Some((
+            pagination.playlist_id,
+            start,
+            pagination.track_ids[start..].to_vec(),
+        ))
+    }
+
+    /// 合并从offset开始的count首歌曲，已加载的部分跳过，返回是否属于该歌单
+    pub fn append_page(
+        &mut self,
+        playlist_id: usize,
+        offset: usize,
+        count: usize,
+        tracks: &[Track],
+    ) -> bool {
+        let pagination = match self.pagination.as_mut() {
+            Some(pagination) if pagination.playlist_id == playlist_id => pagination,
+            _ => return false,
+        };
+        let end = (offset + count).min(pagination.track_ids.len());
+        if offset <= pagination.loaded && pagination.loaded < end {
+            let ids = pagination.track_ids[pagination.loaded..end]
+                .iter()
+                .collect::<HashSet<_>>();
+            self.tracks
+                .extend(tracks.iter().filter(|t| ids.contains(&t.id)).cloned());
+            pagination.loaded = end;
+        }
+        true
+    }
+
+    /// 加载失败时允许重新请求未加载的部分
+    pub fn reset_requested(&mut self, playlist_id: usize) {
+        if let Some(pagination) = self.pagination.as_mut() {
+            if pagination.playlist_id == playlist_id {
+                pagination.requested = pagination.loaded;
+            }
+        }
+    }
 }
 
 #[derive(Default, Clone)]
@@ -23,6 +118,10 @@ pub struct RecentlyPlayedResp {
     pub data: RecentlyPlayedData,
 }
 
+impl ApiResp for RecentlyPlayedResp {
+    const ERROR: &'static str = "获取最近播放失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RecentlyPlayedData {
@@ -52,6 +151,9 @@ pub enum TableId {
     RecentlyPlayed,
     #[allow(unused)]
     Lyric,
+    Account,
+    Toplist,
+    Playlist,
 }
 
 #[derive(PartialEq)]
@@ -91,3 +193,61 @@ pub struct TableItem {
     pub fee: usize,
     pub format: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{TrackPagination, TrackTable, PLAYLIST_PAGE_SIZE};
+    use crate::model::track::{track, Track};
+
+    fn tracks(ids: std::ops::Range<usize>) -> Vec<Track> {
+        ids.map(track).collect()
+    }
+
+    fn table(total: usize) -> TrackTable {
+        TrackTable {
+            tracks: tracks(0..PLAYLIST_PAGE_SIZE),
+            pagination: Some(TrackPagination {
+                playlist_id: 1,
+                track_ids: (0..total).collect(),
+                loaded: PLAYLIST_PAGE_SIZE,
+                requested: PLAYLIST_PAGE_SIZE,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_next_page() {
+        let mut table = table(250);
+        let (_, offset, ids) = table.next_page(0).unwrap();
+        assert_eq!((offset, ids.len()), (100, 100));
+        // 已请求的页不会重复请求
+        assert_eq!(table.next_page(0), None);
+
+        // 下架的歌曲不返回，也算作已加载
+        let mut page = tracks(100..200);
+        page.remove(10);
+        assert!(table.append_page(1, 100, 100, &page));
+        assert_eq!(table.tracks.len(), 199);
+        assert_eq!(table.next_page(10), None);
+        let (_, offset, ids) = table.next_page(150).unwrap();
+        assert_eq!((offset, ids.len()), (200, 50));
+        assert!(!table.append_page(2, 200, 50, &tracks(200..250)));
+    }
+
+    #[test]
+    fn test_remaining_pages() {
+        let mut table = table(250);
+        let (_, offset, _) = table.next_page(0).unwrap();
+        let (_, _, ids) = table.remaining_pages().unwrap();
+        assert_eq!(ids.len(), 150);
+        // 返回前不重复请求
+        assert_eq!(table.remaining_pages(), None);
+        // 剩余歌曲先返回后，之前请求的页不会重复添加
+        table.append_page(1, 100, 150, &tracks(100..250));
+        table.append_page(1, offset, 100, &tracks(100..200));
+        assert_eq!(table.tracks.len(), 250);
+        assert_eq!(table.total(), 250);
+        assert_eq!(table.remaining_pages(), None);
+    }
+}
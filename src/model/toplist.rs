@@ -0,0 +1,74 @@
+use crate::http::response::ApiResp;
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ToplistResp {
+    pub code: usize,
+    #[serde(default)]
+    pub list: Vec<Toplist>,
+}
+
+impl ApiResp for ToplistResp {
+    const ERROR: &'static str = "获取排行榜失败";
+}
+
+// 排行榜，歌曲通过榜单id按歌单获取
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Toplist {
+    pub id: usize,
+    pub name: String,
+    // 更新频率，如"每天更新"
+    #[serde(default)]
+    pub update_frequency: String,
+    // 榜单前三名，只有官方榜有
+    #[serde(default)]
+    pub tracks: Vec<ToplistTrack>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ToplistTrack {
+    // 歌曲名
+    pub first: String,
+    // 歌手名
+    pub second: String,
+}
+
+impl Toplist {
+    /// 前三名的预览，如"1.再来一杯 - Mr.mo  2.过客 - Mr.mo"
+    pub fn preview(&self) -> String {
+        self.tracks
+            .iter()
+            .take(3)
+            .enumerate()
+            .map(|(i, track)| format!("{}.{} - {}", i + 1, track.first, track.second))
+            .collect::<Vec<String>>()
+            .join("  ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Toplist, ToplistTrack};
+
+    #[test]
+    fn test_preview() {
+        let track = |name: &str| ToplistTrack {
+            first: name.to_string(),
+            second: "Mr.mo".to_string(),
+        };
+        let toplist = Toplist {
+            tracks: vec![
+                track("再来一杯"),
+                track("过客"),
+                track("晚安"),
+                track("早安"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            toplist.preview(),
+            "1.再来一杯 - Mr.mo  2.过客 - Mr.mo  3.晚安 - Mr.mo"
+        );
+        assert_eq!(Toplist::default().preview(), "");
+    }
+}
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use crate::http::response::ApiResp;
 use crate::model::album::Album;
 use crate::model::artist::Artist;
 
@@ -21,6 +22,15 @@ pub struct Track {
     // pub publish_time: i64,
 }
 
+/// 只有id的歌曲，用于测试
+#[cfg(test)]
+pub fn track(id: usize) -> Track {
+    Track {
+        id,
+        ..Default::default()
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TrackUrl {
@@ -30,6 +40,9 @@ pub struct TrackUrl {
     // 1vip收费，0免费
     pub fee: usize,
     pub free_trial_info: Option<FreeTrialInfo>,
+    // 文件的md5，用于校验下载的歌曲是否完整
+    #[serde(default)]
+    pub md5: Option<String>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -45,6 +58,10 @@ pub struct TrackUrlResp {
     pub data: Vec<TrackUrl>,
 }
 
+impl ApiResp for TrackUrlResp {
+    const ERROR: &'static str = "播放失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RecommendedTracks {
@@ -60,6 +77,10 @@ pub struct RecommendedTracksResp {
     pub data: Option<RecommendedTracks>,
 }
 
+impl ApiResp for RecommendedTracksResp {
+    const ERROR: &'static str = "获取每日推荐失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct LyricResp {
     pub code: usize,
@@ -67,6 +88,10 @@ pub struct LyricResp {
     pub tlyric: Lrc,
 }
 
+impl ApiResp for LyricResp {
+    const ERROR: &'static str = "获取歌词失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Lrc {
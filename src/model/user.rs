@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use crate::http::response::ApiResp;
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserProfile {
@@ -14,9 +16,17 @@ pub struct UserAccountResp {
     pub profile: Option<UserProfile>,
 }
 
+impl ApiResp for UserAccountResp {
+    const ERROR: &'static str = "获取用户信息失败";
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LikeTrackIdListResp {
     pub code: usize,
     pub ids: HashSet<usize>,
 }
+
+impl ApiResp for LikeTrackIdListResp {
+    const ERROR: &'static str = "获取喜欢的歌曲失败";
+}
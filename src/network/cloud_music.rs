@@ -1,25 +1,36 @@
 use std::collections::HashSet;
-use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{anyhow, Result};
-use tokio::sync::Mutex;
+use serde_json::json;
+use tokio::try_join;
 
-use crate::app::App;
 use crate::handlers::search::{
     SearchAlbumResp, SearchArtistResp, SearchPlaylistResp, SearchResult, SearchTrackResp,
     SearchType,
 };
 use crate::http::api::CloudMusicApi;
+use crate::http::error::ApiError;
+use crate::http::response::{ApiResp, ImplicitResult};
 use crate::model::album::{Album, AlbumResp};
 use crate::model::artist::{
     Artist, ArtistAlbumResp, ArtistSublistResp, ArtistTracksResp, SimiArtistsResp,
 };
-use crate::model::login::LoginResp;
+use crate::model::discover::{
+    HighqualityTagsResp, PlaylistCategory, PlaylistCatlistResp, PlaylistOrder, TopPlaylistResp,
+    DISCOVER_PAGE_SIZE,
+};
+use crate::model::fm::PersonalFmResp;
+use crate::model::intelligence::IntelligenceResp;
+use crate::model::login::{
+    CaptchaResp, CountriesResp, Country, LoginResp, QrCheckResp, QrKeyResp, QrLoginState,
+};
 use crate::model::playlist::{
     Playlist, PlaylistDetail, PlaylistDetailResp, PlaylistTracksResp, UserPlaylistResp,
 };
+use crate::model::radio::SimiSongResp;
 use crate::model::table::RecentlyPlayedResp;
+use crate::model::toplist::{Toplist, ToplistResp};
 use crate::model::track::{Lyric, LyricResp, RecommendedTracksResp, Track, TrackUrl, TrackUrlResp};
 use crate::model::user::{LikeTrackIdListResp, UserAccountResp, UserProfile};
 
@@ -30,29 +41,16 @@ pub struct CloudMusic {
 
 impl CloudMusic {
     pub async fn current_user(&self) -> Result<Option<UserProfile>> {
-        return match self.api.user_account().await {
-            Ok(resp) => {
-                let resp = serde_json::from_slice::<UserAccountResp>(resp.data())?;
-                if resp.code != 200 && resp.profile.is_none() {
-                    return Err(anyhow!("网络连接错误"));
-                }
-                Ok(resp.profile)
-            }
-            _ => Err(anyhow!("网络连接错误")),
-        };
+        let resp = self.api.user_account().await?;
+        Ok(resp.parse::<UserAccountResp>()?.profile)
     }
 
     pub async fn current_user_playlists<L: Into<Option<u32>>, O: Into<Option<u32>>>(
         &self,
         limit: L,
         offset: O,
-        app: &Arc<Mutex<App>>,
+        user_id: usize,
     ) -> Result<Vec<Playlist>> {
-        let app = app.lock().await;
-        let error_msg = "歌单获取失败";
-        if app.user.is_none() {
-            return Err(anyhow!(error_msg));
-        }
         let mut params = serde_json::Map::new();
         let limit = serde_json::Value::String(limit.into().unwrap_or(50).to_string());
         let offset = serde_json::Value::String(offset.into().unwrap_or(0).to_string());
@@ -60,29 +58,23 @@ impl CloudMusic {
         params.insert("offset".to_owned(), offset);
         let params = serde_json::Value::Object(params);
 
-        let resp = self
-            .api
-            .user_playlist(app.user.as_ref().unwrap().user_id, Some(params))
-            .await;
-        if let Ok(resp) = resp {
-            let resp = serde_json::from_slice::<UserPlaylistResp>(resp.data())?;
-            if resp.code == 200 {
-                return Ok(resp.playlist);
-            }
-        }
-        Err(anyhow!(error_msg))
+        let resp = self.api.user_playlist(user_id, Some(params)).await?;
+        Ok(resp.parse::<UserPlaylistResp>()?.playlist)
     }
 
-    #[allow(unused)]
-    pub async fn playlist_tracks(&self, playlist_id: usize) -> Result<PlaylistDetail> {
-        let resp = self.api.playlist_detail(playlist_id, None).await;
-        if let Ok(resp) = resp {
-            let result = serde_json::from_slice::<PlaylistDetailResp>(resp.data())?;
-            if result.code == 200 {
-                return Ok(result.playlist.unwrap());
-            }
-        }
-        Err(anyhow!("获取歌单歌曲失败"))
+    /// 获取歌单详情，limit: 同时返回的歌曲数，全部歌曲的id在track_ids中
+    pub async fn playlist_tracks(
+        &self,
+        playlist_id: usize,
+        limit: usize,
+    ) -> Result<PlaylistDetail> {
+        let resp = self
+            .api
+            .playlist_detail(playlist_id, Some(json!({ "n": limit })))
+            .await?;
+        resp.parse::<PlaylistDetailResp>()?
+            .playlist
+            .ok_or_else(|| anyhow!(PlaylistDetailResp::ERROR))
     }
 
     #[allow(unused)]
@@ -92,34 +84,30 @@ impl CloudMusic {
         offset: usize,
         limit: usize,
     ) -> Result<PlaylistTracksResp> {
-        let resp = self.api.playlist_tracks(playlist_id, offset, limit).await;
-        if let Ok(resp) = resp {
-            let result = serde_json::from_slice::<PlaylistTracksResp>(resp.data())?;
-            if result.code == 200 {
-                return Ok(result);
-            }
-        }
-        Err(anyhow!("获取歌单歌曲失败"))
+        let resp = self.api.playlist_tracks(playlist_id, offset, limit).await?;
+        resp.parse::<PlaylistTracksResp>()
+    }
+
+    // 按id获取歌曲详情，用于分页加载歌单
+    pub async fn song_detail(&self, track_ids: &[usize]) -> Result<Vec<Track>> {
+        let resp = self.api.song_detail(track_ids).await?;
+        Ok(resp.parse::<PlaylistTracksResp>()?.tracks)
     }
 
     pub async fn song_url(&self, track_id: Vec<usize>) -> Result<Vec<TrackUrl>> {
-        if let Ok(resp) = self.api.song_url(&track_id).await {
-            let song_url_resp = serde_json::from_slice::<TrackUrlResp>(resp.data())?;
-            let track_url = song_url_resp.data.get(0);
-            if song_url_resp.code == 200 {
-                if let Some(track_url) = track_url {
-                    if track_url.url.is_some() {
-                        return Ok(song_url_resp.data);
-                    }
-                }
-            }
+        let resp = self.api.song_url(&track_id).await?;
+        let resp = resp.parse::<TrackUrlResp>()?;
+        match resp.data.get(0) {
+            Some(track_url) if track_url.url.is_some() => Ok(resp.data),
+            // 接口成功但没有播放地址，是因为版权限制无法播放
+            Some(_) => Err(ApiError::Copyright.into()),
+            None => Err(anyhow!(TrackUrlResp::ERROR)),
         }
-        return Err(anyhow!("播放失败"));
     }
 
     pub async fn recent_song_list(&self, limit: u32) -> Result<Vec<Track>> {
-        let resp = self.api.recent_song_list(limit).await.unwrap();
-        let resp = serde_json::from_slice::<RecentlyPlayedResp>(resp.data()).unwrap();
+        let resp = self.api.recent_song_list(limit).await?;
+        let resp = resp.parse::<RecentlyPlayedResp>()?;
         let recently_list = resp.data.list;
         let tracks = recently_list
             .into_iter()
@@ -130,33 +118,18 @@ impl CloudMusic {
 
     pub async fn recommend_song_list(&self) -> Result<Vec<Track>> {
         let resp = self.api.recommend_song_list().await?;
-        let resp = serde_json::from_slice::<RecommendedTracksResp>(resp.data())?;
-        if resp.code != 200 {
-            return Ok(vec![]);
-        }
-        match resp.data {
-            Some(data) => Ok(data.daily_songs),
-            None => Ok(vec![]),
-        }
+        let resp = resp.parse::<RecommendedTracksResp>()?;
+        Ok(resp.data.map(|data| data.daily_songs).unwrap_or_default())
     }
 
     pub async fn like_track_id_list(&self, user_id: usize) -> Result<HashSet<usize>> {
-        if let Ok(resp) = self.api.like_list(user_id).await {
-            let resp = serde_json::from_slice::<LikeTrackIdListResp>(resp.data())?;
-            if resp.code != 200 {
-                return Err(anyhow!("网络连接错误"));
-            }
-            return Ok(resp.ids);
-        }
-        return Err(anyhow!("网络连接错误"));
+        let resp = self.api.like_list(user_id).await?;
+        Ok(resp.parse::<LikeTrackIdListResp>()?.ids)
     }
 
     pub async fn lyric(&self, track_id: usize) -> Result<Vec<Lyric>> {
         let resp = self.api.lyric(track_id).await?;
-        let resp = serde_json::from_slice::<LyricResp>(resp.data())?;
-        if resp.code != 200 {
-            return Err(anyhow!("get song lyric failed."));
-        }
+        let resp = resp.parse::<LyricResp>()?;
 
         let mut lyric: Vec<Lyric> = Vec::new();
         let re = regex::Regex::new(r#"((?:\[\w+:\w+[:\.]\w+\])+)(.*?)$"#).unwrap();
@@ -197,23 +170,100 @@ impl CloudMusic {
     }
 
     pub async fn toggle_like_track(&self, track_id: usize, like: bool) -> Result<()> {
-        let resp = self.api.like(track_id, like).await;
-        match resp {
-            Err(e) => {
-                return Err(anyhow!(e));
+        let resp = self.api.like(track_id, like).await?;
+        resp.parse_or::<ImplicitResult>(if like {
+            "喜欢歌曲失败"
+        } else {
+            "取消喜欢歌曲失败"
+        })?;
+        Ok(())
+    }
+
+    pub async fn personal_fm(&self) -> Result<Vec<Track>> {
+        let resp = self.api.personal_fm().await?;
+        Ok(resp.parse::<PersonalFmResp>()?.data)
+    }
+
+    /// 歌单分类和精品歌单标签
+    pub async fn playlist_categories(&self) -> Result<(Vec<String>, Vec<String>)> {
+        let (catlist, tags) = try_join!(
+            self.api.playlist_catlist(),
+            self.api.playlist_highquality_tags()
+        )?;
+        let names = |categories: Vec<PlaylistCategory>| {
+            categories
+                .into_iter()
+                .map(|c| c.name)
+                .collect::<Vec<String>>()
+        };
+        Ok((
+            names(catlist.parse::<PlaylistCatlistResp>()?.sub),
+            names(tags.parse::<HighqualityTagsResp>()?.tags),
+        ))
+    }
+
+    /// 按分类和排序获取一页歌单
+    pub async fn top_playlists(
+        &self,
+        category: &str,
+        order: PlaylistOrder,
+        offset: usize,
+        before: u64,
+    ) -> Result<TopPlaylistResp> {
+        let resp = match order {
+            PlaylistOrder::Hot => {
+                self.api
+                    .top_playlist(category, "hot", DISCOVER_PAGE_SIZE, offset)
+                    .await?
             }
-            Ok(resp) => {
-                let res = resp.deserialize_to_implict();
-                if !res.code == 200 {
-                    return Err(anyhow!(if like {
-                        "喜欢歌曲失败"
-                    } else {
-                        "取消喜欢歌曲失败"
-                    }));
-                }
+            PlaylistOrder::New => {
+                self.api
+                    .top_playlist(category, "new", DISCOVER_PAGE_SIZE, offset)
+                    .await?
             }
-        }
+            PlaylistOrder::HighQuality => {
+                self.api
+                    .top_playlist_highquality(category, DISCOVER_PAGE_SIZE, before)
+                    .await?
+            }
+        };
+        resp.parse::<TopPlaylistResp>()
+    }
+
+    /// 所有排行榜，官方榜带有前三名歌曲
+    pub async fn toplists(&self) -> Result<Vec<Toplist>> {
+        let resp = self.api.toplist_detail().await?;
+        Ok(resp.parse::<ToplistResp>()?.list)
+    }
+
+    /// 相似歌曲
+    pub async fn simi_songs(&self, track_id: usize) -> Result<Vec<Track>> {
+        let resp = self.api.simi_songs(track_id).await?;
+        Ok(resp.parse::<SimiSongResp>()?.songs)
+    }
+
+    /// 心动模式的歌曲，返回歌曲和是否为推荐的歌曲
+    pub async fn intelligence_list(
+        &self,
+        track_id: usize,
+        playlist_id: usize,
+        start_id: usize,
+    ) -> Result<Vec<(Track, bool)>> {
+        let resp = self
+            .api
+            .playmode_intelligence_list(track_id, playlist_id, start_id)
+            .await?;
+        Ok(resp
+            .parse::<IntelligenceResp>()?
+            .data
+            .into_iter()
+            .filter_map(|item| item.song_info.map(|track| (track, item.recommended)))
+            .collect())
+    }
 
+    pub async fn fm_trash(&self, track_id: usize) -> Result<()> {
+        let resp = self.api.fm_trash(track_id).await?;
+        resp.parse_or::<ImplicitResult>("移除歌曲失败")?;
         Ok(())
     }
 
@@ -223,129 +273,180 @@ impl CloudMusic {
         search_type: SearchType,
     ) -> Result<SearchResult> {
         let resp = self.api.cloud_search(keyword, search_type, None).await?;
-        match search_type {
-            SearchType::Track => {
-                let resp = serde_json::from_slice::<SearchTrackResp>(resp.data())?;
-                if resp.code == 200 {
-                    if let Some(res) = resp.result {
-                        return Ok(SearchResult::Tracks(res.songs));
-                    }
-                }
-            }
-            SearchType::Album => {
-                if let Ok(resp) = serde_json::from_slice::<SearchAlbumResp>(resp.data()) {
-                    if resp.code == 200 {
-                        if let Some(res) = resp.result {
-                            return Ok(SearchResult::Albums(res.albums));
-                        }
-                    }
-                }
-            }
-            SearchType::Artist => {
-                if let Ok(resp) = serde_json::from_slice::<SearchArtistResp>(resp.data()) {
-                    if resp.code == 200 {
-                        if let Some(res) = resp.result {
-                            return Ok(SearchResult::Artists(res.artists));
-                        }
-                    }
-                }
-            }
-            SearchType::Playlist => {
-                if let Ok(resp) = serde_json::from_slice::<SearchPlaylistResp>(resp.data()) {
-                    if resp.code == 200 {
-                        if let Some(res) = resp.result {
-                            return Ok(SearchResult::Playlists(res.playlists));
-                        }
-                    }
-                }
-            }
-        }
-        Ok(SearchResult::new(search_type))
+        // 没有搜索结果时result为空
+        let result = match search_type {
+            SearchType::Track => resp
+                .parse::<SearchTrackResp>()?
+                .result
+                .map(|res| SearchResult::Tracks(res.songs)),
+            SearchType::Album => resp
+                .parse::<SearchAlbumResp>()?
+                .result
+                .map(|res| SearchResult::Albums(res.albums)),
+            SearchType::Artist => resp
+                .parse::<SearchArtistResp>()?
+                .result
+                .map(|res| SearchResult::Artists(res.artists)),
+            SearchType::Playlist => resp
+                .parse::<SearchPlaylistResp>()?
+                .result
+                .map(|res| SearchResult::Playlists(res.playlists)),
+        };
+        Ok(result.unwrap_or_else(|| SearchResult::new(search_type)))
     }
 
     pub async fn playlist_subscribe(&self, id: usize, is_subscribe: bool) -> Result<()> {
         let resp = self.api.playlist_subscribe(id, is_subscribe).await?;
-        let code = resp.deserialize_to_implict().code;
-        if code != 200 {
-            return Err(anyhow!("收藏歌单失败！"));
-        }
+        resp.parse_or::<ImplicitResult>(if is_subscribe {
+            "收藏歌单失败！"
+        } else {
+            "取消收藏歌单失败！"
+        })?;
         Ok(())
     }
 
     pub async fn artist_sublist(&self) -> Result<Vec<Artist>> {
-        if let Ok(resp) = self.api.artist_sublist().await {
-            let res = serde_json::from_slice::<ArtistSublistResp>(resp.data()).unwrap();
-            if res.code == 200 {
-                return Ok(res.data);
-            }
-        }
-        Err(anyhow!("获取关注歌手失败"))
+        let resp = self.api.artist_sublist().await?;
+        Ok(resp.parse::<ArtistSublistResp>()?.data)
     }
 
     #[allow(unused)]
     pub async fn artist_tracks(&self, artist_id: usize) -> Result<Vec<Track>> {
-        if let Ok(resp) = self.api.artist_tracks(artist_id).await {
-            let resp = serde_json::from_slice::<ArtistTracksResp>(resp.data()).unwrap();
-            if resp.code == 200 {
-                return Ok(resp.tracks);
-            }
-        }
-        Err(anyhow!("获取歌曲失败"))
+        let resp = self.api.artist_tracks(artist_id).await?;
+        Ok(resp.parse::<ArtistTracksResp>()?.tracks)
     }
 
     pub async fn artist_albums(&self, artist_id: usize) -> Result<Vec<Album>> {
-        if let Ok(resp) = self.api.artist_albums(artist_id).await {
-            let resp = serde_json::from_slice::<ArtistAlbumResp>(resp.data()).unwrap();
-            if resp.code == 200 {
-                return Ok(resp.hot_albums);
-            }
-        }
-        Err(anyhow!("获取相似歌手失败"))
+        let resp = self.api.artist_albums(artist_id).await?;
+        Ok(resp.parse::<ArtistAlbumResp>()?.hot_albums)
     }
 
     pub async fn simi_artists(&self, artist_id: usize) -> Result<Vec<Artist>> {
-        if let Ok(resp) = self.api.simi_artists(artist_id).await {
-            let resp = serde_json::from_slice::<SimiArtistsResp>(resp.data()).unwrap();
-            if resp.code == 200 {
-                return Ok(resp.artists);
-            }
-        }
-        Err(anyhow!("获取相似歌手失败"))
+        let resp = self.api.simi_artists(artist_id).await?;
+        Ok(resp.parse::<SimiArtistsResp>()?.artists)
     }
 
     pub async fn album(&self, album_id: usize) -> Result<(Vec<Track>, Album)> {
-        if let Ok(resp) = self.api.album(album_id).await {
-            let resp = serde_json::from_slice::<AlbumResp>(resp.data()).unwrap();
-            if resp.code == 200 {
-                return Ok((resp.songs, resp.album));
-            }
-        }
-        Err(anyhow!("获取相似歌手失败"))
+        let resp = self.api.album(album_id).await?;
+        let resp = resp.parse::<AlbumResp>()?;
+        Ok((resp.songs, resp.album))
     }
 
     pub async fn artist_sub(&self, artist_id: usize) -> Result<()> {
-        if let Ok(artist_sublist) = self.artist_sublist().await {
-            let mut is_sub: bool = true;
-            for x in &artist_sublist {
-                if x.id == artist_id {
-                    is_sub = false;
-                }
-            }
-            if (self.api.artist_sub(artist_id, is_sub).await).is_ok() {
-                return Ok(());
+        let artist_sublist = self.artist_sublist().await?;
+        let is_sub = !artist_sublist.iter().any(|x| x.id == artist_id);
+        let resp = self.api.artist_sub(artist_id, is_sub).await?;
+        resp.parse_or::<ImplicitResult>(if is_sub {
+            "收藏歌手失败"
+        } else {
+            "取消收藏歌手失败"
+        })?;
+        Ok(())
+    }
+
+    pub async fn login(
+        &self,
+        country_code: &str,
+        phone: &str,
+        password: &str,
+    ) -> Result<UserProfile> {
+        let resp = self.api.login_phone(country_code, phone, password).await?;
+        resp.parse::<LoginResp>()?
+            .profile
+            .ok_or_else(|| anyhow!(LoginResp::ERROR))
+    }
+
+    // 退出登录，本地的cookie由调用方清除
+    pub async fn logout(&self) -> Result<()> {
+        self.api.logout().await?;
+        Ok(())
+    }
+
+    // 导入cookie登录，校验cookie有效后写入本地
+    pub async fn login_cookies(&self, cookies: &str) -> Result<UserProfile> {
+        let api = CloudMusicApi::with_cookies(cookies)?;
+        // 网络错误和限流等原样返回，只有未登录时才是cookie无效
+        let resp = api
+            .user_account()
+            .await
+            .and_then(|resp| resp.parse::<UserAccountResp>())
+            .map_err(|e| match e.downcast_ref::<ApiError>() {
+                Some(ApiError::Auth) => anyhow!("Cookie无效或已过期"),
+                _ => e,
+            })?;
+        let profile = resp.profile.ok_or_else(|| anyhow!("Cookie无效或已过期"))?;
+        api.save_cookies()?;
+        Ok(profile)
+    }
+
+    pub async fn login_email(&self, email: &str, password: &str) -> Result<UserProfile> {
+        let resp = self.api.login_email(email, password).await?;
+        resp.parse::<LoginResp>()
+            .map_err(email_login_error)?
+            .profile
+            .ok_or_else(|| anyhow!(LoginResp::ERROR))
+    }
+
+    pub async fn send_captcha(&self, country_code: &str, phone: &str) -> Result<()> {
+        let resp = self.api.captcha_sent(country_code, phone).await?;
+        resp.parse_or::<CaptchaResp>("验证码发送失败……")?;
+        Ok(())
+    }
+
+    pub async fn login_captcha(
+        &self,
+        country_code: &str,
+        phone: &str,
+        captcha: &str,
+    ) -> Result<UserProfile> {
+        let resp = self
+            .api
+            .captcha_verify(country_code, phone, captcha)
+            .await?;
+        resp.parse::<CaptchaResp>()?;
+        let resp = self
+            .api
+            .login_phone_captcha(country_code, phone, captcha)
+            .await?;
+        resp.parse::<LoginResp>()?
+            .profile
+            .ok_or_else(|| anyhow!(LoginResp::ERROR))
+    }
+
+    // 获取国家/地区列表，去掉分组间重复的国家
+    pub async fn countries(&self) -> Result<Vec<Country>> {
+        let resp = self.api.countries_code_list().await?;
+        let resp = resp.parse::<CountriesResp>()?;
+        let mut countries: Vec<Country> = vec![];
+        for country in resp.data.into_iter().flat_map(|g| g.country_list) {
+            if !countries
+                .iter()
+                .any(|c| c.locale == country.locale && c.code == country.code)
+            {
+                countries.push(country);
             }
         }
-        Err(anyhow!("收藏或取消收藏歌手失败"))
+        Ok(countries)
     }
 
-    pub async fn login(&self, phone: &str, password: &str) -> Result<UserProfile> {
-        if let Ok(resp) = self.api.login_phone(phone, password).await {
-            let resp = serde_json::from_slice::<LoginResp>(resp.data()).unwrap();
-            if resp.code == 200 && resp.profile.is_some() {
-                return Ok(resp.profile.unwrap());
-            }
+    // 获取扫码登录的key和二维码内容
+    pub async fn login_qr_code(&self) -> Result<(String, String)> {
+        let resp = self.api.login_qr_key().await?;
+        let resp = resp.parse::<QrKeyResp>()?;
+        if resp.unikey.is_empty() {
+            return Err(anyhow!(QrKeyResp::ERROR));
         }
-        return Err(anyhow!("登录失败……"));
+        let url = self.api.login_qr_create(&resp.unikey);
+        Ok((resp.unikey, url))
+    }
+
+    pub async fn login_qr_check(&self, key: &str) -> Result<QrLoginState> {
+        let resp = self.api.login_qr_check(key).await?;
+        // 800~803是扫码状态而不是错误，不能用parse按code判断
+
+        let resp = serde_json::from_slice::<QrCheckResp>(resp.data())?;
+        QrLoginState::from_code(resp.code)
+            .ok_or_else(|| anyhow!(resp.message.unwrap_or_else(|| "登录失败……".to_string())))
     }
 
     pub async fn weblog(&self, track_id: usize) {
@@ -365,29 +466,406 @@ impl CloudMusic {
     }
 }
 
+// 邮箱登录失败且服务端没有返回信息时，按code给出具体原因
+fn email_login_error(e: anyhow::Error) -> anyhow::Error {
+    match e.downcast::<ApiError>() {
+        Ok(ApiError::Api { code, msg }) if msg == LoginResp::ERROR => {
+            let msg = match code {
+                501 => "邮箱账号不存在",
+                502 => "邮箱或密码错误",
+                _ => LoginResp::ERROR,
+            };
+            ApiError::Api {
+                code,
+                msg: msg.to_owned(),
+            }
+            .into()
+        }
+        Ok(e) => e.into(),
+        Err(e) => e,
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use pad::{Alignment, PadStr};
+    use serde_json::json;
 
+    use crate::http::api::CloudMusicApi;
+    use crate::http::error::ApiError;
+    use crate::http::route::API_ROUTE;
+    use crate::http::transport::{track_json, FixtureTransport, WithTempDir};
+    use crate::model::discover::PlaylistOrder;
     use crate::model::track::Lyric;
     use crate::network::cloud_music::CloudMusic;
 
-    #[tokio::test(flavor = "multi_thread")]
-    async fn test_login() {
-        let result = CloudMusic::default().login("xxx", "xxx").await;
-        println!("{:#?}", result.unwrap());
+    fn cloud_music(transport: FixtureTransport) -> WithTempDir<CloudMusic> {
+        CloudMusicApi::with_transport(Arc::new(transport)).map(|api| CloudMusic { api })
     }
 
-    #[tokio::test(flavor = "multi_thread")]
+    #[tokio::test]
     async fn test_recommend_song_list() {
-        let result = CloudMusic::default().recommend_song_list().await;
-        println!("{:#?}", result.unwrap());
-    }
-
-    #[tokio::test(flavor = "multi_thread")]
+        let result = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["recommend_songs"],
+            json!({
+                "code": 200,
+                "data": { "dailySongs": [track_json(1, "再来一杯")], "orderSongs": [] }
+            }),
+        ))
+        .recommend_song_list()
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].name, "再来一杯");
+    }
+
+    #[tokio::test]
     async fn test_lyric() {
-        let result = CloudMusic::default().lyric(1479526505).await;
-        println!("{:#?}", result.unwrap());
+        let result = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["lyric"],
+            json!({
+                "code": 200,
+                "lrc": { "lyric": "[00:28.23]没有人可以诉说苦闷与悲伤\n[00:23.14]曾经我觉得我被世间遗忘\n" },
+                "tlyric": { "lyric": "" }
+            }),
+        ))
+        .lyric(527629786)
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 2);
+        // 按时间排序
+        assert_eq!(result[0].lyric, "曾经我觉得我被世间遗忘");
+    }
+
+    #[tokio::test]
+    async fn test_playlist_page_tracks() {
+        let tracks = cloud_music(
+            FixtureTransport::new()
+                .route(
+                    API_ROUTE["playlist_detail"],
+                    json!({
+                        "code": 200,
+                        "playlist": {
+                            "id": 498339500,
+                            "name": "我喜欢的音乐",
+                            "trackIds": [{ "id": 1 }],
+                            "userId": 354192143
+                        }
+                    }),
+                )
+                .route(
+                    API_ROUTE["song_detail"],
+                    json!({ "code": 200, "songs": [track_json(1, "再来一杯")] }),
+                ),
+        )
+        .playlist_page_tracks(498339500, 0, 10)
+        .await
+        .unwrap();
+        assert_eq!(tracks.tracks.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_playlist_first_page() {
+        let playlist = cloud_music(FixtureTransport::new().route_with_params(
+            API_ROUTE["playlist_detail"],
+            json!({ "n": 1 }),
+            json!({
+                "code": 200,
+                "playlist": {
+                    "id": 498339500,
+                    "name": "我喜欢的音乐",
+                    "tracks": [track_json(1, "再来一杯")],
+                    "trackIds": [{ "id": 1 }, { "id": 2 }],
+                    "userId": 354192143
+                }
+            }),
+        ))
+        .playlist_tracks(498339500, 1)
+        .await
+        .unwrap();
+        assert_eq!(playlist.tracks.len(), 1);
+        assert_eq!(playlist.track_ids.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_song_detail() {
+        let tracks = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["song_detail"],
+            json!({ "code": 200, "songs": [track_json(2, "再来一杯")] }),
+        ))
+        .song_detail(&[2])
+        .await
+        .unwrap();
+        assert_eq!(tracks[0].id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_personal_fm() {
+        let tracks = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["personal_fm"],
+            json!({
+                "code": 200,
+                "data": [{
+                    "id": 1,
+                    "name": "再来一杯",
+                    "artists": [{ "id": 12279635, "name": "Mr.mo" }],
+                    "album": { "id": 32311, "name": "再来一杯" },
+                    "duration": 330000,
+                    "fee": 0,
+                    "popularity": 100.0
+                }]
+            }),
+        ))
+        .personal_fm()
+        .await
+        .unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].duration, 330000);
+    }
+
+    #[tokio::test]
+    async fn test_intelligence_list() {
+        let items = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["playmode_intelligence_list"],
+            json!({
+                "code": 200,
+                "data": [{
+                    "id": 2,
+                    "recommended": true,
+                    "songInfo": {
+                        "id": 2,
+                        "name": "过客",
+                        "ar": [{ "id": 12279635, "name": "Mr.mo" }],
+                        "al": { "id": 32311, "name": "再来一杯" },
+                        "dt": 240000,
+                        "fee": 8,
+                        "pop": 100.0
+                    }
+                }, {
+                    "id": 3,
+                    "recommended": false,
+                    "songInfo": null
+                }]
+            }),
+        ))
+        .intelligence_list(1, 10, 1)
+        .await
+        .unwrap();
+        // 没有歌曲信息的跳过
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].0.id, 2);
+        assert!(items[0].1);
+    }
+
+    #[tokio::test]
+    async fn test_top_playlists() {
+        let resp = cloud_music(FixtureTransport::new().route_with_params(
+            API_ROUTE["top_playlist_highquality"],
+            json!({ "cat": "华语", "lasttime": 0 }),
+            json!({
+                "code": 200,
+                "playlists": [{
+                    "id": 24381616,
+                    "name": "华语精选",
+                    "subscribed": false,
+                    "creator": { "userId": 1, "nickname": "云音乐" },
+                    "playCount": 123456,
+                    "trackCount": 50
+                }],
+                "total": 120,
+                "more": true,
+                "lasttime": 1700000000000u64
+            }),
+        ))
+        .top_playlists("华语", PlaylistOrder::HighQuality, 0, 0)
+        .await
+        .unwrap();
+        assert_eq!(
+            resp.playlists[0].creator.as_ref().unwrap().nickname,
+            "云音乐"
+        );
+        assert_eq!(resp.playlists[0].track_count, 50);
+        assert_eq!(resp.lasttime, 1700000000000);
+    }
+
+    #[tokio::test]
+    async fn test_top_playlists_order() {
+        let playlists = |id: usize| {
+            json!({
+                "code": 200,
+                "playlists": [{ "id": id, "name": "歌单" }],
+                "total": 120,
+                "more": true
+            })
+        };
+        let cloud_music = cloud_music(
+            FixtureTransport::new()
+                .route_with_params(
+                    API_ROUTE["top_playlist"],
+                    json!({ "cat": "华语", "order": "hot", "limit": 50, "offset": 50 }),
+                    playlists(1),
+                )
+                .route_with_params(
+                    API_ROUTE["top_playlist"],
+                    json!({ "cat": "华语", "order": "new", "limit": 50, "offset": 100 }),
+                    playlists(2),
+                ),
+        );
+        let hot = cloud_music
+            .top_playlists("华语", PlaylistOrder::Hot, 50, 0)
+            .await
+            .unwrap();
+        assert_eq!(hot.playlists[0].id, 1);
+        let new = cloud_music
+            .top_playlists("华语", PlaylistOrder::New, 100, 0)
+            .await
+            .unwrap();
+        assert_eq!(new.playlists[0].id, 2);
+        assert!(cloud_music
+            .top_playlists("华语", PlaylistOrder::Hot, 0, 0)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_toplists() {
+        let toplists = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["toplist_detail"],
+            json!({
+                "code": 200,
+                "list": [{
+                    "id": 19723756,
+                    "name": "飙升榜",
+                    "updateFrequency": "每天更新",
+                    "tracks": [{ "first": "再来一杯", "second": "Mr.mo" }]
+                }, {
+                    "id": 3778678,
+                    "name": "热歌榜",
+                    "updateFrequency": "每周四更新",
+                    "tracks": []
+                }]
+            }),
+        ))
+        .toplists()
+        .await
+        .unwrap();
+        assert_eq!(toplists.len(), 2);
+        assert_eq!(toplists[0].update_frequency, "每天更新");
+        assert_eq!(toplists[0].preview(), "1.再来一杯 - Mr.mo");
+    }
+
+    #[tokio::test]
+    async fn test_simi_songs() {
+        let tracks = cloud_music(FixtureTransport::new().route_with_params(
+            API_ROUTE["simi_song"],
+            json!({ "songid": 1 }),
+            json!({
+                "code": 200,
+                "songs": [{
+                    "id": 2,
+                    "name": "过客",
+                    "artists": [{ "id": 12279635, "name": "Mr.mo" }],
+                    "album": { "id": 32311, "name": "再来一杯" },
+                    "duration": 240000,
+                    "fee": 8,
+                    "popularity": 100.0
+                }]
+            }),
+        ))
+        .simi_songs(1)
+        .await
+        .unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].name, "过客");
+    }
+
+    #[tokio::test]
+    async fn test_artist_sublist() {
+        let artists = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["artist_sublist"],
+            json!({ "code": 200, "data": [{ "id": 12279635, "name": "Mr.mo" }], "hasMore": false }),
+        ))
+        .artist_sublist()
+        .await
+        .unwrap();
+        assert_eq!(artists.len(), 1);
+        assert_eq!(artists[0].name.as_deref(), Some("Mr.mo"));
+    }
+
+    #[tokio::test]
+    async fn test_album() {
+        let (tracks, album) = cloud_music(FixtureTransport::new().route(
+            &API_ROUTE["album"].replace("${query.id}", "32311"),
+            json!({
+                "code": 200,
+                "songs": [track_json(1, "再来一杯")],
+                "album": { "id": 32311, "name": "再来一杯", "artist": { "id": 12279635, "name": "Mr.mo" } }
+            }),
+        ))
+        .album(32311)
+        .await
+        .unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(album.id, 32311);
+    }
+
+    #[tokio::test]
+    async fn test_song_url_without_url() {
+        let result = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["song_url"],
+            json!({
+                "code": 200,
+                "data": [{ "id": 1, "url": null, "br": 0, "fee": 1, "freeTrialInfo": null }]
+            }),
+        ))
+        .song_url(vec![1])
+        .await;
+        assert_eq!(
+            result.unwrap_err().downcast_ref(),
+            Some(&ApiError::Copyright)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_toggle_like_track_failed() {
+        let result =
+            cloud_music(FixtureTransport::new().route(API_ROUTE["like"], json!({ "code": 400 })))
+                .toggle_like_track(1, true)
+                .await;
+        assert_eq!(result.unwrap_err().to_string(), "喜欢歌曲失败");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited() {
+        let result = cloud_music(FixtureTransport::new().route(
+            API_ROUTE["artist_sublist"],
+            json!({ "code": 405, "message": "操作频繁，请稍候再试" }),
+        ))
+        .artist_sublist()
+        .await;
+        assert_eq!(
+            result.unwrap_err().downcast_ref(),
+            Some(&ApiError::RateLimited)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_login_email_error() {
+        let login = |body| async move {
+            cloud_music(FixtureTransport::new().route(API_ROUTE["login"], body))
+                .login_email("user@163.com", "password")
+                .await
+                .unwrap_err()
+        };
+        let err = login(json!({ "code": -460, "message": "Cheating" })).await;
+        assert_eq!(err.downcast_ref(), Some(&ApiError::RateLimited));
+        // 服务端没有返回信息时按code提示
+        let err = login(json!({ "code": 501 })).await;
+        assert_eq!(err.to_string(), "邮箱账号不存在");
+        let err = login(json!({ "code": 502, "msg": "密码错误" })).await;
+        assert_eq!(err.to_string(), "密码错误");
     }
 
     #[test]
@@ -419,6 +897,29 @@ mod tests {
         let s = "I'm over here".pad_to_width_with_alignment(50, Alignment::Middle);
         println!("{}", s);
     }
+}
+
+#[cfg(all(test, feature = "live-tests"))]
+mod live_tests {
+    use crate::network::cloud_music::CloudMusic;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_login() {
+        let result = CloudMusic::default().login("86", "xxx", "xxx").await;
+        println!("{:#?}", result.unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_recommend_song_list() {
+        let result = CloudMusic::default().recommend_song_list().await;
+        println!("{:#?}", result.unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lyric() {
+        let result = CloudMusic::default().lyric(1479526505).await;
+        println!("{:#?}", result.unwrap());
+    }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_playlist_page_tracks() {
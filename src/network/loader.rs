@@ -0,0 +1,503 @@
+use std::collections::HashSet;
+use std::ops::Not;
+use std::sync::Arc;
+
+use anyhow::Error;
+use tokio::sync::Mutex;
+use tokio::try_join;
+
+use crate::app::{ActiveBlock, App, RouteId};
+use crate::event::IoEvent;
+use crate::handlers::search::{SearchResult, SearchResults, SearchType};
+use crate::model::album::{Album, AlbumDetail};
+use crate::model::artist::{ArtistBlock, ArtistDetail};
+use crate::model::context::TrackTableContext;
+use crate::model::discover::PlaylistOrder;
+use crate::model::login::QrLogin;
+use crate::model::table::{TrackPagination, TrackTable, PLAYLIST_PAGE_SIZE};
+use crate::network::cloud_music::CloudMusic;
+use crate::util::render_qr_code;
+
+/// 不涉及播放器的网络请求，可以在独立的任务中并发执行
+#[derive(Clone)]
+pub struct Loader {
+    pub app: Arc<Mutex<App>>,
+    pub cloud_music: Arc<CloudMusic>,
+    pub large_search_limit: u32,
+}
+
+impl Loader {
+    pub async fn send_captcha(&self, country_code: String, phone: String) {
+        if let Err(e) = self.cloud_music.send_captcha(&country_code, &phone).await {
+            let mut app = self.app.lock().await;
+            // 发送失败时允许立即重新发送
+            app.login_info.captcha_sent_time = None;
+            app.handle_error(e);
+        }
+    }
+
+    pub async fn load_countries(&self) {
+        match self.cloud_music.countries().await {
+            Ok(countries) => {
+                let mut app = self.app.lock().await;
+                app.login_info.countries = countries;
+                app.login_info.country_selected_index = 0;
+            }
+            Err(e) => self.handle_error(e).await,
+        }
+    }
+
+    pub async fn load_login_qr_code(&self) {
+        match self.cloud_music.login_qr_code().await {
+            Ok((key, url)) => match render_qr_code(&url) {
+                Ok(image) => {
+                    let mut app = self.app.lock().await;
+                    app.login_info.qr_login = Some(QrLogin::new(key, image));
+                }
+                Err(e) => self.handle_error(e).await,
+            },
+            Err(e) => self.handle_error(e).await,
+        }
+    }
+
+    pub async fn toggle_sub_artist(&self, artist_id: usize) {
+        match self.cloud_music.artist_sub(artist_id).await {
+            Ok(_) => {
+                self.load_artist_sublist().await;
+            }
+            Err(e) => self.handle_error(e).await,
+        }
+    }
+
+    pub async fn load_album_tracks(&self, album: Box<Album>) {
+        match self.cloud_music.album(album.id).await {
+            Ok(res) => {
+                let mut app = self.app.lock().await;
+                app.album_detail = Some(AlbumDetail {
+                    album: res.1,
+                    tracks: res.0,
+                    selected_track_index: 0,
+                });
+                app.push_navigation_stack(RouteId::AlbumTracks, ActiveBlock::AlbumTracks);
+            }
+            Err(e) => self.handle_error(e).await,
+        }
+    }
+
+    pub async fn load_artist_detail(&self, artist_id: usize, artist_name: String) {
+        let artist_tracks = self.cloud_music.artist_tracks(artist_id);
+        let artist_albums = self.cloud_music.artist_albums(artist_id);
+        let simi_artists = self.cloud_music.simi_artists(artist_id);
+
+        match try_join!(artist_tracks, artist_albums, simi_artists) {
+            Ok((artist_tracks, artist_albums, simi_artists)) => {
+                let mut app = self.app.lock().await;
+                app.artist_detail = Some(ArtistDetail {
+                    artist_name,
+                    tracks: artist_tracks,
+                    albums: artist_albums,
+                    simi_artists,
+                    selected_album_index: 0,
+                    selected_simi_artist_index: 0,
+                    selected_track_index: 0,
+                    artist_detail_selected_block: ArtistBlock::Tracks,
+                    artist_detail_hovered_block: ArtistBlock::Tracks,
+                });
+            }
+            Err(e) => self.handle_error(e).await,
+        }
+    }
+
+    pub async fn load_artist_sublist(&self) {
+        match self.cloud_music.artist_sublist().await {
+            Ok(artists) => {
+                let mut app = self.app.lock().await;
+                if artists.is_empty().not() {
+                    app.artist_sub_ids_set =
+                        artists.iter().map(|it| it.id).collect::<HashSet<usize>>();
+                }
+                app.artists = artists;
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
+        }
+    }
+
+    pub async fn weblog(&self, track_id: usize) {
+        self.cloud_music.weblog(track_id).await;
+    }
+
+    pub async fn playlist_subscribe(&self, playlist_id: usize) {
+        let is_subscribe = {
+            let app = self.app.lock().await;
+            app.sub_playlists.as_ref().map_or(true, |playlists| {
+                playlists.iter().all(|x| x.id != playlist_id)
+            })
+        };
+        let resp = self
+            .cloud_music
+            .playlist_subscribe(playlist_id, is_subscribe)
+            .await;
+        match resp {
+            Ok(_) => {
+                // 重新获取用户歌单
+                self.app.lock().await.dispatch(IoEvent::GetPlaylists);
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
+        }
+    }
+
+    pub async fn load_search_results(&self, keyword: &str) {
+        let search_tracks = self.cloud_music.cloud_search(keyword, SearchType::Track);
+        let search_albums = self.cloud_music.cloud_search(keyword, SearchType::Album);
+        let search_artists = self.cloud_music.cloud_search(keyword, SearchType::Artist);
+        let search_playlists = self.cloud_music.cloud_search(keyword, SearchType::Playlist);
+
+        match try_join!(
+            search_tracks,
+            search_albums,
+            search_artists,
+            search_playlists
+        ) {
+            Ok((
+                SearchResult::Tracks(track_results),
+                SearchResult::Albums(album_results),
+                SearchResult::Artists(artist_results),
+                SearchResult::Playlists(playlist_results),
+            )) => {
+                let mut app = self.app.lock().await;
+                app.search_results = SearchResults {
+                    tracks: Some(track_results),
+                    albums: Some(album_results),
+                    artists: Some(artist_results),
+                    playlists: Some(playlist_results),
+                    ..Default::default()
+                }
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
+            _ => {}
+        };
+    }
+
+    pub async fn toggle_like_track(&self, track_id: usize) {
+        let like = !self
+            .app
+            .lock()
+            .await
+            .liked_track_ids_set
+            .contains(&track_id);
+        let result = self.cloud_music.toggle_like_track(track_id, like).await;
+        let mut app = self.app.lock().await;
+        match result {
+            Ok(_) if like => {
+                app.liked_track_ids_set.insert(track_id);
+            }
+            Ok(_) => {
+                app.liked_track_ids_set.remove(&track_id);
+            }
+            Err(e) => app.handle_error(e),
+        }
+    }
+
+    pub async fn load_track_lyric(&self, track_id: usize, is_active_block: bool) {
+        let lyric = self.cloud_music.lyric(track_id).await;
+        let mut app = self.app.lock().await;
+        match lyric {
+            Ok(lyric) => {
+                app.lyric_index = 0;
+                app.lyric = Some(lyric);
+            }
+            Err(_) => {
+                app.lyric_index = 0;
+                app.lyric = None;
+            }
+        }
+        if is_active_block {
+            app.push_navigation_stack(RouteId::Lyric, ActiveBlock::Lyric);
+        }
+    }
+
+    pub async fn load_like_track_id_list(&self) {
+        if let Some(user_id) = self.current_user_id().await {
+            if let Ok(liked_track_ids) = self.cloud_music.like_track_id_list(user_id).await {
+                self.app.lock().await.liked_track_ids_set = liked_track_ids;
+            }
+        }
+    }
+
+    pub async fn load_recommend_tracks(&self) {
+        match self.cloud_music.recommend_song_list().await {
+            Ok(tracks) => {
+                let mut app = self.app.lock().await;
+                app.track_table = TrackTable {
+                    tracks,
+                    selected_index: 0,
+                    context: Some(TrackTableContext::RecommendedTracks),
+                    ..Default::default()
+                };
+                app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
+                app.title = String::from("每日推荐");
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
+        }
+    }
+
+    // 歌单详情只返回第一页歌曲和全部歌曲的id，其余歌曲在滚动时按需加载
+    pub async fn load_playlist_tracks(&self, playlist_id: usize) {
+        self.load_playlist_table(
+            playlist_id,
+            TrackTableContext::MyPlaylists,
+            String::from("歌曲列表"),
+        )
+        .await
+    }
+
+    /// 按歌单加载歌曲并显示在歌曲表格中，排行榜也以歌单的形式获取
+    pub async fn load_playlist_table(
+        &self,
+        playlist_id: usize,
+        context: TrackTableContext,
+        title: String,
+    ) {
+        let result = self
+            .cloud_music
+            .playlist_tracks(playlist_id, PLAYLIST_PAGE_SIZE)
+            .await;
+        match result {
+            Ok(playlist) => {
+                let track_ids = playlist
+                    .track_ids
+                    .iter()
+                    .map(|id| id.id)
+                    .collect::<Vec<usize>>();
+                let mut tracks = playlist.tracks;
+                tracks.truncate(PLAYLIST_PAGE_SIZE);
+                let pagination = if track_ids.is_empty() {
+                    None
+                } else {
+                    let loaded = if tracks.is_empty() {
+                        0
+                    } else {
+                        track_ids.len().min(PLAYLIST_PAGE_SIZE)
+                    };
+                    Some(TrackPagination {
+                        playlist_id,
+                        track_ids,
+                        loaded,
+                        requested: loaded,
+                    })
+                };
+                let mut table = TrackTable {
+                    tracks,
+                    selected_index: 0,
+                    context: Some(context),
+                    pagination,
+                };
+                let next_page = table.next_page(0);
+                let mut app = self.app.lock().await;
+                app.track_table = table;
+                app.title = title;
+                app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
+                drop(app);
+                // 预加载下一页
+                if let Some((playlist_id, offset, track_ids)) = next_page {
+                    self.load_playlist_page(playlist_id, offset, track_ids, false)
+                        .await;
+                }
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
+        }
+    }
+
+    pub async fn load_toplists(&self) {
+        match self.cloud_music.toplists().await {
+            Ok(toplists) => {
+                let mut app = self.app.lock().await;
+                app.toplists = toplists;
+                app.toplists_selected_index = 0;
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
+        }
+    }
+
+    pub async fn load_playlist_categories(&self) {
+        match self.cloud_music.playlist_categories().await {
+            Ok((categories, highquality_tags)) => {
+                let mut app = self.app.lock().await;
+                app.discover.set_categories(categories, highquality_tags);
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
+        }
+    }
+
+    pub async fn load_discover_playlists(
+        &self,
+        category: String,
+        order: PlaylistOrder,
+        offset: usize,
+        before: u64,
+    ) {
+        let result = self
+            .cloud_music
+            .top_playlists(&category, order, offset, before)
+            .await;
+        let mut app = self.app.lock().await;
+        match result {
+            Ok(resp) => app.discover.append_page(&category, order, resp),
+            Err(e) => {
+                app.discover.is_fetching = false;
+                app.handle_error(e);
+            }
+        }
+    }
+
+    pub async fn load_playlist_page(
+        &self,
+        playlist_id: usize,
+        offset: usize,
+        track_ids: Vec<usize>,
+        jump_to_end: bool,
+    ) {
+        // 跳转到末尾时需要加载的歌曲较多，分批请求
+        let mut tracks = vec![];
+        for ids in track_ids.chunks(PLAYLIST_PAGE_SIZE * 5) {
+            match self.cloud_music.song_detail(ids).await {
+                Ok(page) => tracks.extend(page),
+                Err(e) => {
+                    let mut app = self.app.lock().await;
+                    app.track_table.reset_requested(playlist_id);
+                    app.current_play_tracks.reset_requested(playlist_id);
+                    app.handle_error(e);
+                    return;
+                }
+            }
+        }
+        let mut app = self.app.lock().await;
+        let count = track_ids.len();
+        if app
+            .track_table
+            .append_page(playlist_id, offset, count, &tracks)
+            && jump_to_end
+        {
+            app.track_table.selected_index = app.track_table.tracks.len().saturating_sub(1);
+        }
+        // 正在播放该歌单时同步到播放列表
+        app.current_play_tracks
+            .append_page(playlist_id, offset, count, &tracks);
+    }
+
+    pub async fn load_personal_fm(&self) {
+        let result = self.cloud_music.personal_fm().await;
+        let mut app = self.app.lock().await;
+        app.personal_fm.is_fetching = false;
+        match result {
+            Ok(tracks) => {
+                app.personal_fm.extend(tracks);
+                // 仍在播放FM时，继续播放等待中的歌曲
+                if app.personal_fm.play_when_loaded && app.is_playing_fm() {
+                    if let Some(track) = app.personal_fm.current().cloned() {
+                        app.personal_fm.play_when_loaded = false;
+                        app.dispatch(IoEvent::StartPlayback(track));
+                    }
+                }
+            }
+            Err(e) => app.handle_error(e),
+        }
+    }
+
+    pub async fn load_simi_songs(&self, track_id: usize) {
+        let result = self.cloud_music.simi_songs(track_id).await;
+        let mut app = self.app.lock().await;
+        if app.radio.seed_id != track_id {
+            return;
+        }
+        app.radio.is_fetching = false;
+        match result {
+            Ok(tracks) => app.extend_radio(track_id, tracks),
+            Err(e) => app.handle_error(e),
+        }
+    }
+
+    pub async fn load_intelligence_list(&self, track_id: usize, playlist_id: usize) {
+        let result = self
+            .cloud_music
+            .intelligence_list(track_id, playlist_id, track_id)
+            .await;
+        let mut app = self.app.lock().await;
+        // 请求期间已退出心动模式或发起了新的请求时丢弃
+        if app.intelligence.seed_id != track_id {
+            return;
+        }
+        app.intelligence.is_fetching = false;
+        match result {
+            Ok(items) => app.extend_intelligence_list(track_id, items),
+            Err(e) => app.handle_error(e),
+        }
+    }
+
+    pub async fn fm_trash(&self, track_id: usize) {
+        if let Err(e) = self.cloud_music.fm_trash(track_id).await {
+            self.handle_error(e).await;
+        }
+    }
+
+    pub async fn load_current_user_playlists(&self) {
+        let user_id = match self.current_user_id().await {
+            Some(user_id) => user_id,
+            None => return,
+        };
+        let result = self
+            .cloud_music
+            .current_user_playlists(self.large_search_limit, None, user_id)
+            .await;
+        match result {
+            Ok(list) => {
+                let mut app = self.app.lock().await;
+                // 我创建的歌单列表
+                let mut my_playlists = vec![];
+                // 我收藏的歌单列表
+                let mut subscribed_playlists = vec![];
+                for (i, play_list) in list.into_iter().enumerate() {
+                    if i != 0 {
+                        match play_list.subscribed {
+                            true => subscribed_playlists.push(play_list),
+                            false => my_playlists.push(play_list),
+                        }
+                    } else {
+                        app.my_like_playlist_id = play_list.id;
+                    }
+                }
+                app.playlists = Some(my_playlists);
+                app.selected_playlist_index = Some(0);
+
+                app.sub_playlists = Some(subscribed_playlists);
+                app.selected_sub_playlist_index = Some(0);
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
+        }
+    }
+
+    pub async fn current_user_id(&self) -> Option<usize> {
+        self.app.lock().await.user.as_ref().map(|u| u.user_id)
+    }
+
+    pub async fn handle_error(&self, e: Error) {
+        let mut app = self.app.lock().await;
+        app.handle_error(e);
+    }
+}
@@ -1,10 +1,10 @@
-use std::collections::HashSet;
-use std::ops::Not;
+use std::collections::HashMap;
+use std::future::Future;
 use std::panic::PanicInfo;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use backtrace::Backtrace;
 use crossterm::{
     event::DisableMouseCapture,
@@ -12,24 +12,72 @@ use crossterm::{
     style::Print,
     terminal::{disable_raw_mode, LeaveAlternateScreen},
 };
+use futures::FutureExt;
 use tokio::sync::Mutex;
-use tokio::try_join;
+use tokio::task::JoinHandle;
 
 use crate::app::{ActiveBlock, App, RouteId};
 use crate::event::IoEvent;
-use crate::handlers::search::{SearchResult, SearchResults, SearchType};
-use crate::model::album::{Album, AlbumDetail};
-use crate::model::artist::{ArtistBlock, ArtistDetail};
+use crate::http::{account_manager, clear_http_cache};
 use crate::model::context::{CurrentlyPlaybackContext, TrackTableContext};
 use crate::model::enums::{CurrentlyPlayingType, RepeatState};
-use crate::model::login::LoginForm;
+use crate::model::login::{CaptchaLoginForm, EmailLoginForm, LoginForm, QrLoginState};
 use crate::model::table::TrackTable;
-use crate::model::track::Track;
+use crate::model::track::{Track, TrackUrl};
 use crate::network::cloud_music::CloudMusic;
+use crate::network::loader::Loader;
 use crate::player::Nplayer;
 use crate::util::{create_artist_string2, get_music_path};
 
 pub(crate) mod cloud_music;
+mod loader;
+
+// 可被新的请求取代的请求类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TaskKind {
+    Search,
+    PlaylistTracks,
+    Lyric,
+    ArtistDetail,
+    AlbumTracks,
+    SimiSongs,
+    DiscoverPlaylists,
+}
+
+// 正在执行的请求，切换账号或退出登录时全部取消
+#[derive(Default)]
+struct Tasks {
+    // 可被新的同类请求取代的请求
+    latest: HashMap<TaskKind, JoinHandle<()>>,
+    // 其他请求
+    others: Vec<JoinHandle<()>>,
+}
+
+impl Tasks {
+    fn track(&mut self, handle: JoinHandle<()>) {
+        // 顺便移除已经结束的请求
+        self.others.retain_mut(|task| task.now_or_never().is_none());
+        self.others.push(handle);
+    }
+
+    // 同一类请求只保留最新的一个，取消之前的请求，避免旧的结果覆盖新的结果
+    fn track_latest(&mut self, kind: TaskKind, handle: JoinHandle<()>) {
+        if let Some(previous) = self.latest.insert(kind, handle) {
+            previous.abort();
+        }
+    }
+
+    fn cancel(&mut self) {
+        for task in self
+            .latest
+            .drain()
+            .map(|(_, task)| task)
+            .chain(self.others.drain(..))
+        {
+            task.abort();
+        }
+    }
+}
 
 pub struct Network<'a> {
     // 最大搜索限制
@@ -38,7 +86,9 @@ pub struct Network<'a> {
     small_search_limit: u32,
     pub app: &'a Arc<Mutex<App>>,
     pub player: Nplayer,
-    pub cloud_music: CloudMusic,
+    pub cloud_music: Arc<CloudMusic>,
+    // 正在执行的请求
+    tasks: Tasks,
 }
 
 impl<'a> Network<'a> {
@@ -47,27 +97,26 @@ impl<'a> Network<'a> {
             large_search_limit: 20,
             small_search_limit: 4,
             app,
-            cloud_music: CloudMusic::default(),
+            cloud_music: Arc::new(CloudMusic::default()),
             player: Nplayer::new(),
+            tasks: Tasks::default(),
         }
     }
 
     pub async fn handle_network_event(&mut self, io_event: IoEvent) {
+        let io_event = match self.spawn_concurrent(io_event) {
+            Some(io_event) => io_event,
+            None => return,
+        };
         match io_event {
             // IoEvent::GetSearchResults(search_term) => {}
             IoEvent::UpdateSearchLimits(large_search_limit, small_search_limit) => {
                 self.large_search_limit = large_search_limit;
                 self.small_search_limit = small_search_limit;
             }
-            IoEvent::GetPlaylists => {
-                self.load_current_user_playlists().await;
-            }
             IoEvent::GetUser => {
                 self.load_user().await;
             }
-            IoEvent::GetPlaylistTracks(playlist_id) => {
-                self.load_playlist_tracks(playlist_id).await;
-            }
             IoEvent::StartPlayback(track) => {
                 self.start_playback(track).await;
             }
@@ -77,29 +126,21 @@ impl<'a> Network<'a> {
             IoEvent::GetRecentlyPlayed => {
                 self.load_recently_played().await;
             }
-            IoEvent::GetRecommendTracks => {
-                self.load_recommend_tracks().await;
-            }
             IoEvent::DecreaseVolume => {
                 self.decrease_volume().await;
             }
             IoEvent::IncreaseVolume => {
                 self.increase_volume().await;
             }
-            IoEvent::GetLikeList => {
-                self.load_like_track_id_list().await;
-            }
-            IoEvent::GetLyric(track_id, is_active_block) => {
-                self.load_track_lyric(track_id, is_active_block).await;
-            }
+            // 收藏类操作按顺序执行，避免连续切换时结果错乱
             IoEvent::ToggleLikeTrack(track_id) => {
-                self.toggle_like_track(track_id).await;
-            }
-            IoEvent::GetSearchResults(keyword) => {
-                self.load_search_results(&keyword).await;
+                self.loader().toggle_like_track(track_id).await;
             }
             IoEvent::ToggleSubscribePlaylist(playlist_id) => {
-                self.playlist_subscribe(playlist_id).await;
+                self.loader().playlist_subscribe(playlist_id).await;
+            }
+            IoEvent::ToggleSubscribeArtist(artist_id) => {
+                self.loader().toggle_sub_artist(artist_id).await;
             }
             IoEvent::SeekForwards => {
                 self.seek(true).await;
@@ -107,23 +148,20 @@ impl<'a> Network<'a> {
             IoEvent::SeekBackForwards => {
                 self.seek(false).await;
             }
-            IoEvent::WebLog(track_id) => {
-                self.weblog(track_id).await;
-            }
-            IoEvent::GetArtistSubList => {
-                self.load_artist_sublist().await;
+            IoEvent::Login(login_form) => {
+                self.login_app(login_form).await;
             }
-            IoEvent::GetArtistDetail(artist_id, artist_name) => {
-                self.load_artist_detail(artist_id, artist_name).await;
+            IoEvent::CaptchaLogin(form) => {
+                self.login_app_by_captcha(form).await;
             }
-            IoEvent::GetAlbumTracks(album) => {
-                self.load_album_tracks(album).await;
+            IoEvent::EmailLogin(form) => {
+                self.login_app_by_email(form).await;
             }
-            IoEvent::ToggleSubscribeArtist(artist_id) => {
-                self.toggle_sub_artist(artist_id).await;
+            IoEvent::CookieLogin(cookies) => {
+                self.login_app_by_cookies(cookies).await;
             }
-            IoEvent::Login(login_form) => {
-                self.login_app(login_form).await;
+            IoEvent::CheckLoginQrCode(key) => {
+                self.check_login_qr_code(key).await;
             }
             IoEvent::AddToQueue(track) => {
                 self.add_to_queue(track).await;
@@ -131,12 +169,159 @@ impl<'a> Network<'a> {
             IoEvent::ResetPlay => {
                 self.reset_play().await;
             }
+            IoEvent::Logout => {
+                self.logout().await;
+            }
+            IoEvent::SwitchAccount(user_id) => {
+                self.switch_account(user_id).await;
+            }
+            _ => {}
         }
 
         let mut app = self.app.lock().await;
         app.is_loading = false;
     }
 
+    // 与播放器无关的请求在独立的任务中并发执行，返回需要按顺序执行的事件
+    fn spawn_concurrent(&mut self, io_event: IoEvent) -> Option<IoEvent> {
+        let loader = self.loader();
+        match io_event {
+            IoEvent::GetPlaylists => {
+                self.spawn(async move { loader.load_current_user_playlists().await });
+            }
+            IoEvent::GetPlaylistTracks(playlist_id) => {
+                self.spawn_latest(TaskKind::PlaylistTracks, async move {
+                    loader.load_playlist_tracks(playlist_id).await
+                });
+            }
+            IoEvent::GetToplists => {
+                self.spawn(async move { loader.load_toplists().await });
+            }
+            IoEvent::GetPlaylistCategories => {
+                self.spawn(async move { loader.load_playlist_categories().await });
+            }
+            IoEvent::GetDiscoverPlaylists(category, order, offset, before) => {
+                // 切换分类或排序时取消之前的请求
+                self.spawn_latest(TaskKind::DiscoverPlaylists, async move {
+                    loader
+                        .load_discover_playlists(category, order, offset, before)
+                        .await
+                });
+            }
+            IoEvent::GetToplistTracks(toplist_id, name) => {
+                self.spawn_latest(TaskKind::PlaylistTracks, async move {
+                    loader
+                        .load_playlist_table(toplist_id, TrackTableContext::Toplist, name)
+                        .await
+                });
+            }
+            IoEvent::GetPlaylistTracksPage(playlist_id, offset, track_ids, jump_to_end) => {
+                self.spawn(async move {
+                    loader
+                        .load_playlist_page(playlist_id, offset, track_ids, jump_to_end)
+                        .await
+                });
+            }
+            IoEvent::GetPersonalFm => {
+                self.spawn(async move { loader.load_personal_fm().await });
+            }
+            IoEvent::FmTrash(track_id) => {
+                self.spawn(async move { loader.fm_trash(track_id).await });
+            }
+            IoEvent::GetSimiSongs(track_id) => {
+                // 开始新的电台时取消之前电台的请求
+                self.spawn_latest(TaskKind::SimiSongs, async move {
+                    loader.load_simi_songs(track_id).await
+                });
+            }
+            IoEvent::GetIntelligenceList(track_id, playlist_id) => {
+                self.spawn(
+                    async move { loader.load_intelligence_list(track_id, playlist_id).await },
+                );
+            }
+            IoEvent::GetRecommendTracks => {
+                self.spawn(async move { loader.load_recommend_tracks().await });
+            }
+            IoEvent::GetLyric(track_id, is_active_block) => {
+                self.spawn_latest(TaskKind::Lyric, async move {
+                    loader.load_track_lyric(track_id, is_active_block).await
+                });
+            }
+            IoEvent::GetSearchResults(keyword) => {
+                self.spawn_latest(TaskKind::Search, async move {
+                    loader.load_search_results(&keyword).await
+                });
+            }
+            IoEvent::WebLog(track_id) => {
+                self.spawn(async move { loader.weblog(track_id).await });
+            }
+            IoEvent::GetArtistSubList => {
+                self.spawn(async move { loader.load_artist_sublist().await });
+            }
+            IoEvent::GetArtistDetail(artist_id, artist_name) => {
+                self.spawn_latest(TaskKind::ArtistDetail, async move {
+                    loader.load_artist_detail(artist_id, artist_name).await
+                });
+            }
+            IoEvent::GetAlbumTracks(album) => {
+                self.spawn_latest(TaskKind::AlbumTracks, async move {
+                    loader.load_album_tracks(album).await
+                });
+            }
+            IoEvent::SendCaptcha(country_code, phone) => {
+                self.spawn(async move { loader.send_captcha(country_code, phone).await });
+            }
+            IoEvent::GetCountries => {
+                self.spawn(async move { loader.load_countries().await });
+            }
+            IoEvent::GetLoginQrCode => {
+                self.spawn(async move { loader.load_login_qr_code().await });
+            }
+            io_event => return Some(io_event),
+        }
+        None
+    }
+
+    fn spawn_task<F>(&self, future: F) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let app = self.app.clone();
+        tokio::spawn(async move {
+            future.await;
+            app.lock().await.is_loading = false;
+        })
+    }
+
+    fn spawn<F>(&mut self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let handle = self.spawn_task(future);
+        self.tasks.track(handle);
+    }
+
+    fn spawn_latest<F>(&mut self, kind: TaskKind, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let handle = self.spawn_task(future);
+        self.tasks.track_latest(kind, handle);
+    }
+
+    // 切换账号时之前账号的请求结果不再需要
+    fn cancel_tasks(&mut self) {
+        self.tasks.cancel();
+    }
+
+    fn loader(&self) -> Loader {
+        Loader {
+            app: self.app.clone(),
+            cloud_music: self.cloud_music.clone(),
+            large_search_limit: self.large_search_limit,
+        }
+    }
+
     pub async fn reset_play(&mut self) {
         let mut app = self.app.lock().await;
         self.player.seek(Duration::from_secs(0));
@@ -152,10 +337,16 @@ impl<'a> Network<'a> {
         // println!("{:?}", login_form);
         match self
             .cloud_music
-            .login(login_form.phone.as_str(), login_form.password.as_str())
+            .login(
+                login_form.country_code.as_str(),
+                login_form.phone.as_str(),
+                login_form.password.as_str(),
+            )
             .await
         {
             Ok(profile) => {
+                // 重新加载登录后写入的cookie
+                self.cloud_music = Arc::new(CloudMusic::default());
                 let mut app = self.app.lock().await;
                 app.login_info.is_login_success = true;
                 println!("{:?}", profile);
@@ -167,73 +358,96 @@ impl<'a> Network<'a> {
         }
     }
 
-    async fn toggle_sub_artist(&mut self, artist_id: usize) {
-        match self.cloud_music.artist_sub(artist_id).await {
-            Ok(_) => {
-                self.load_artist_sublist().await;
+    async fn login_app_by_captcha(&mut self, form: CaptchaLoginForm) {
+        match self
+            .cloud_music
+            .login_captcha(
+                form.country_code.as_str(),
+                form.phone.as_str(),
+                form.captcha.as_str(),
+            )
+            .await
+        {
+            Ok(profile) => {
+                // 重新加载登录后写入的cookie
+                self.cloud_music = Arc::new(CloudMusic::default());
+                let mut app = self.app.lock().await;
+                app.login_info.is_login_success = true;
+                app.user = Some(profile);
+            }
+            Err(e) => {
+                self.handle_error(e).await;
             }
-            Err(e) => self.handle_error(e).await,
         }
     }
 
-    async fn load_album_tracks(&mut self, album: Box<Album>) {
-        match self.cloud_music.album(album.id).await {
-            Ok(res) => {
+    async fn login_app_by_email(&mut self, form: EmailLoginForm) {
+        match self
+            .cloud_music
+            .login_email(form.email.as_str(), form.password.as_str())
+            .await
+        {
+            Ok(profile) => {
+                // 重新加载登录后写入的cookie
+                self.cloud_music = Arc::new(CloudMusic::default());
                 let mut app = self.app.lock().await;
-                app.album_detail = Some(AlbumDetail {
-                    album: res.1,
-                    tracks: res.0,
-                    selected_track_index: 0,
-                });
-                app.push_navigation_stack(RouteId::AlbumTracks, ActiveBlock::AlbumTracks);
+                app.login_info.is_login_success = true;
+                app.user = Some(profile);
+            }
+            Err(e) => {
+                self.handle_error(e).await;
             }
-            Err(e) => self.handle_error(e).await,
         }
     }
 
-    async fn load_artist_detail(&mut self, artist_id: usize, artist_name: String) {
-        let artist_tracks = self.cloud_music.artist_tracks(artist_id);
-        let artist_albums = self.cloud_music.artist_albums(artist_id);
-        let simi_artists = self.cloud_music.simi_artists(artist_id);
-
-        if let Ok((artist_tracks, artist_albums, simi_artists)) =
-            try_join!(artist_tracks, artist_albums, simi_artists)
-        {
-            let mut app = self.app.lock().await;
-            app.artist_detail = Some(ArtistDetail {
-                artist_name,
-                tracks: artist_tracks,
-                albums: artist_albums,
-                simi_artists,
-                selected_album_index: 0,
-                selected_simi_artist_index: 0,
-                selected_track_index: 0,
-                artist_detail_selected_block: ArtistBlock::Tracks,
-                artist_detail_hovered_block: ArtistBlock::Tracks,
-            });
+    async fn login_app_by_cookies(&mut self, cookies: String) {
+        match self.cloud_music.login_cookies(&cookies).await {
+            Ok(profile) => {
+                // 重新加载登录后写入的cookie
+                self.cloud_music = Arc::new(CloudMusic::default());
+                let mut app = self.app.lock().await;
+                app.login_info.is_login_success = true;
+                app.user = Some(profile);
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+            }
         }
     }
 
-    async fn load_artist_sublist(&mut self) {
-        let mut app = self.app.lock().await;
-        match self.cloud_music.artist_sublist().await {
-            Ok(artists) => {
-                if artists.is_empty().not() {
-                    app.artist_sub_ids_set =
-                        artists.iter().map(|it| it.id).collect::<HashSet<usize>>();
+    async fn check_login_qr_code(&mut self, key: String) {
+        match self.cloud_music.login_qr_check(&key).await {
+            Ok(state) => {
+                if state == QrLoginState::Confirmed {
+                    // 重新加载登录后写入的cookie
+                    self.cloud_music = Arc::new(CloudMusic::default());
+                }
+                let mut app = self.app.lock().await;
+                match &mut app.login_info.qr_login {
+                    // 二维码已刷新，忽略旧的查询结果
+                    Some(qr_login) if qr_login.key == key => {
+                        qr_login.state = state;
+                    }
+                    _ => return,
+                }
+                if state == QrLoginState::Confirmed {
+                    app.login_info.is_login_success = true;
                 }
-                app.artists = artists;
             }
             Err(e) => {
-                self.handle_error(e).await;
+                let mut app = self.app.lock().await;
+                match &mut app.login_info.qr_login {
+                    // 停止轮询，避免每次查询都重复显示错误
+                    Some(qr_login) if qr_login.key == key => {
+                        qr_login.state = QrLoginState::Failed;
+                    }
+                    _ => return,
+                }
+                app.handle_error(e);
             }
         }
     }
 
-    async fn weblog(&mut self, track_id: usize) {
-        self.cloud_music.weblog(track_id).await;
-    }
-
     async fn seek(&mut self, is_forward: bool) {
         let mut app = self.app.lock().await;
         if app.current_playback_context.clone().is_some() {
@@ -268,64 +482,6 @@ impl<'a> Network<'a> {
         }
     }
 
-    async fn playlist_subscribe(&mut self, playlist_id: usize) {
-        let mut app = self.app.lock().await;
-        let playlists = app.sub_playlists.clone().unwrap();
-        let mut is_subscribe = true;
-        for x in playlists {
-            if x.id == playlist_id {
-                is_subscribe = false;
-            }
-        }
-        let resp = self
-            .cloud_music
-            .playlist_subscribe(playlist_id, is_subscribe)
-            .await;
-        match resp {
-            Ok(_) => {
-                // 重新获取用户歌单
-                app.dispatch(IoEvent::GetPlaylists);
-            }
-            Err(e) => {
-                self.handle_error(e).await;
-            }
-        }
-    }
-
-    async fn load_search_results(&mut self, keyword: &str) {
-        let search_tracks = self.cloud_music.cloud_search(keyword, SearchType::Track);
-        let search_albums = self.cloud_music.cloud_search(keyword, SearchType::Album);
-        let search_artists = self.cloud_music.cloud_search(keyword, SearchType::Artist);
-        let search_playlists = self.cloud_music.cloud_search(keyword, SearchType::Playlist);
-
-        match try_join!(
-            search_tracks,
-            search_albums,
-            search_artists,
-            search_playlists
-        ) {
-            Ok((
-                SearchResult::Tracks(track_results),
-                SearchResult::Albums(album_results),
-                SearchResult::Artists(artist_results),
-                SearchResult::Playlists(playlist_results),
-            )) => {
-                let mut app = self.app.lock().await;
-                app.search_results = SearchResults {
-                    tracks: Some(track_results),
-                    albums: Some(album_results),
-                    artists: Some(artist_results),
-                    playlists: Some(playlist_results),
-                    ..Default::default()
-                }
-            }
-            Err(e) => {
-                self.handle_error(e).await;
-            }
-            _ => {}
-        };
-    }
-
     async fn load_recently_played(&mut self) {
         let mut app = self.app.lock().await;
         let cache_file_path = app.cache_file_path();
@@ -342,54 +498,13 @@ impl<'a> Network<'a> {
             tracks: table_tracks,
             selected_index: 0,
             context: Some(TrackTableContext::RecentlyPlayed),
+            ..Default::default()
         };
         app.track_table = table;
         app.title = "最近播放".to_string();
         app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
     }
 
-    async fn toggle_like_track(&mut self, track_id: usize) {
-        let mut app = self.app.lock().await;
-        let like_ids_set = app.liked_track_ids_set.clone();
-        let like = !like_ids_set.contains(&track_id);
-        if let Err(e) = self.cloud_music.toggle_like_track(track_id, like).await {
-            app.handle_error(e);
-        };
-        if like {
-            app.liked_track_ids_set.insert(track_id);
-        } else {
-            app.liked_track_ids_set.remove(&track_id);
-        }
-    }
-
-    async fn load_track_lyric(&mut self, track_id: usize, is_active_block: bool) {
-        let mut app = self.app.lock().await;
-        let lyric = self.cloud_music.lyric(track_id).await;
-        match lyric {
-            Ok(lyric) => {
-                app.lyric_index = 0;
-                app.lyric = Some(lyric);
-            }
-            Err(_) => {
-                app.lyric_index = 0;
-                app.lyric = None;
-            }
-        }
-        if is_active_block {
-            app.push_navigation_stack(RouteId::Lyric, ActiveBlock::Lyric);
-        }
-    }
-
-    async fn load_like_track_id_list(&mut self) {
-        let mut app = self.app.lock().await;
-        if let Some(profile) = app.user.clone() {
-            if let Ok(liked_track_ids) = self.cloud_music.like_track_id_list(profile.user_id).await
-            {
-                app.liked_track_ids_set = liked_track_ids;
-            }
-        }
-    }
-
     async fn decrease_volume(&mut self) {
         self.player.decrease_volume();
         let mut app = self.app.lock().await;
@@ -402,24 +517,6 @@ impl<'a> Network<'a> {
         app.volume = self.player.get_volume();
     }
 
-    async fn load_recommend_tracks(&mut self) {
-        match self.cloud_music.recommend_song_list().await {
-            Ok(tracks) => {
-                let mut app = self.app.lock().await;
-                app.track_table = TrackTable {
-                    tracks,
-                    selected_index: 0,
-                    context: Some(TrackTableContext::RecommendedTracks),
-                };
-                app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
-                app.title = String::from("每日推荐");
-            }
-            Err(e) => {
-                self.handle_error(e).await;
-            }
-        }
-    }
-
     #[allow(unused)]
     async fn load_recently_played_from_cloud_music(&mut self, limit: u32) {
         match self.cloud_music.recent_song_list(500).await {
@@ -428,6 +525,7 @@ impl<'a> Network<'a> {
 
                 if limit == 500 {
                     app.track_table.tracks = recent_play_list;
+                    app.track_table.pagination = None;
                     app.track_table.context = Some(TrackTableContext::RecentlyPlayed);
                     app.title = "最近播放".to_string();
                     app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
@@ -454,83 +552,85 @@ impl<'a> Network<'a> {
     async fn toggle_playback(&mut self) {
         let mut app = self.app.lock().await;
         app.retry_count = 0;
-        let context = app.current_playback_context.clone();
-        match context {
-            Some(mut context) => {
-                if self.player.is_playing() {
-                    context.is_playing = false;
-                    app.start_time = Instant::now();
-                    app.current_playback_context = Some(context);
-                    self.player.pause();
-                } else {
-                    match self.player.get_duration() {
-                        Some(_) => {
-                            context.is_playing = true;
-                            app.start_time = Instant::now();
-                            app.current_playback_context = Some(context);
-                            self.player.play();
-                        }
-                        None => {
-                            let track = context.item.as_ref().unwrap();
-                            let track_id = track.id;
-                            if track_id == 0 {
+        let mut context = match app.current_playback_context.clone() {
+            Some(context) => context,
+            None => {
+                self.player.pause();
+                return;
+            }
+        };
+        if self.player.is_playing() {
+            context.is_playing = false;
+            app.start_time = Instant::now();
+            app.current_playback_context = Some(context);
+            self.player.pause();
+            app.volume = self.player.get_volume();
+            return;
+        }
+        match self.player.get_duration() {
+            Some(_) => {
+                context.is_playing = true;
+                app.start_time = Instant::now();
+                app.current_playback_context = Some(context);
+                self.player.play();
+            }
+            None => {
+                let track = context.item.clone().unwrap();
+                let track_id = track.id;
+                if track_id == 0 {
+                    return;
+                }
+                let cache_dir = app.music_cache_dir();
+                let music_name_prefix =
+                    format!("{}-{}", track.name, create_artist_string2(&track.artists));
+                if let Some(path) = get_music_path(None, &cache_dir, &music_name_prefix) {
+                    if path.exists() {
+                        let file_path = path.to_string_lossy().to_string();
+                        match self.player.play_file(file_path) {
+                            Ok(()) => {
+                                context.is_playing = true;
+                                app.start_time = Instant::now();
+                                app.current_playback_context = Some(context);
+
+                                app.dispatch(IoEvent::GetLyric(track_id, false));
                                 return;
                             }
-                            let cache_dir = app.music_cache_dir();
-                            let music_name_prefix =
-                                format!("{}-{}", track.name, create_artist_string2(&track.artists));
-                            if let Some(path) = get_music_path(None, &cache_dir, &music_name_prefix)
-                            {
-                                if path.exists() {
-                                    let file_path = path.to_string_lossy().to_string();
-                                    // println!("{}", file_path);
-                                    match self.player.play_file(file_path) {
-                                        Ok(()) => {
-                                            context.is_playing = true;
-                                            app.start_time = Instant::now();
-                                            app.current_playback_context = Some(context);
-
-                                            app.dispatch(IoEvent::GetLyric(track_id, false));
-                                            return;
-                                        }
-                                        Err(e) => {
-                                            app.handle_error(e);
-                                        }
-                                    }
-                                }
-                            }
-                            match self.cloud_music.song_url(vec![track.id]).await {
-                                Ok(urls) => {
-                                    match self.player.play_url(
-                                        urls.get(0).cloned().unwrap().url.unwrap(),
-                                        cache_dir,
-                                        music_name_prefix,
-                                    ) {
-                                        Ok(()) => {
-                                            context.is_playing = true;
-                                            app.start_time = Instant::now();
-                                            app.current_playback_context = Some(context);
-
-                                            app.dispatch(IoEvent::GetLyric(track_id, false));
-                                        }
-                                        Err(e) => {
-                                            app.handle_error(e);
-                                        }
-                                    }
-                                }
-                                Err(e) => self.handle_error(e).await,
+                            Err(e) => {
+                                app.handle_error(e);
                             }
                         }
                     }
-                    let duration = Duration::from_millis(app.song_progress_ms as u64);
-                    app.start_time = Instant::now() - duration;
                 }
-                app.volume = self.player.get_volume();
-            }
-            None => {
-                self.player.pause();
+                // 获取播放地址和加载歌曲时释放锁，避免期间界面卡住
+                drop(app);
+                let result = match self.cloud_music.song_url(vec![track_id]).await {
+                    Ok(urls) => match urls.into_iter().next() {
+                        Some(TrackUrl {
+                            url: Some(url),
+                            md5,
+                            ..
+                        }) => self.player.play_url(url, md5, cache_dir, music_name_prefix),
+                        _ => Err(anyhow!("获取播放地址失败")),
+                    },
+                    Err(e) => Err(e),
+                };
+                app = self.app.lock().await;
+                match result {
+                    Ok(()) => {
+                        // 请求期间播放状态可能已改变，重新读取后再更新
+                        if let Some(context) = app.current_playback_context.as_mut() {
+                            context.is_playing = true;
+                        }
+                        app.start_time = Instant::now();
+                        app.dispatch(IoEvent::GetLyric(track_id, false));
+                    }
+                    Err(e) => app.handle_error(e),
+                }
             }
         }
+        let duration = Duration::from_millis(app.song_progress_ms as u64);
+        app.start_time = Instant::now() - duration;
+        app.volume = self.player.get_volume();
     }
 
     async fn start_playback(&mut self, mut track: Track) {
@@ -539,9 +639,11 @@ impl<'a> Network<'a> {
         if track_id == 0 {
             return;
         }
-        let mut app = self.app.lock().await;
-        app.retry_count = 0;
-        let cache_dir = app.music_cache_dir();
+        let cache_dir = {
+            let mut app = self.app.lock().await;
+            app.retry_count = 0;
+            app.music_cache_dir()
+        };
         let music_name_prefix = format!("{}-{}", track.name, create_artist_string2(&track.artists));
         let path = get_music_path(None, &cache_dir, &music_name_prefix);
         if let Some(path) = path {
@@ -549,39 +651,19 @@ impl<'a> Network<'a> {
                 let file_path = path.to_string_lossy().to_string();
                 match self.player.play_file(file_path) {
                     Ok(_) => {
-                        match app.current_playback_context.clone() {
-                            Some(mut context) => {
-                                context.is_playing = true;
-                                context.item = Some(track);
-                                app.current_playback_context = Some(context);
-                            }
-                            None => {
-                                let context = CurrentlyPlaybackContext {
-                                    is_playing: true,
-                                    timestamp: 0,
-                                    currently_playing_type: CurrentlyPlayingType::Track,
-                                    repeat_state: RepeatState::Off,
-                                    item: Some(track),
-                                };
-                                app.current_playback_context = Some(context);
-                            }
-                        }
-
-                        app.start_time = Instant::now();
-                        app.volume = self.player.get_volume();
-                        self.cache_play_record(t, &mut *app);
-                        app.dispatch(IoEvent::GetLyric(track_id, false));
-                        app.seek_ms.take();
-                        app.is_fetching_current_playback = false;
+                        let mut app = self.app.lock().await;
+                        self.playback_started(&mut app, track, t);
                         return;
                     }
                     Err(e) => {
-                        app.handle_error(e);
+                        self.handle_error(e).await;
                     }
                 };
             }
         }
-        match self.cloud_music.song_url(vec![track_id]).await {
+        // 请求完成后再加锁更新状态
+        let urls = self.cloud_music.song_url(vec![track_id]).await;
+        match urls {
             Ok(urls) => {
                 if let Some(track_url) = urls.get(0) {
                     if track_url.fee == 1 {
@@ -593,37 +675,16 @@ impl<'a> Network<'a> {
                     }
                     match self.player.play_url(
                         track_url.url.clone().unwrap(),
+                        track_url.md5.clone(),
                         cache_dir,
                         music_name_prefix,
                     ) {
                         Ok(_) => {
-                            match app.current_playback_context.clone() {
-                                Some(mut context) => {
-                                    context.is_playing = true;
-                                    context.item = Some(track);
-                                    app.current_playback_context = Some(context);
-                                }
-                                None => {
-                                    let context = CurrentlyPlaybackContext {
-                                        is_playing: true,
-                                        timestamp: 0,
-                                        currently_playing_type: CurrentlyPlayingType::Track,
-                                        repeat_state: RepeatState::Off,
-                                        item: Some(track),
-                                    };
-                                    app.current_playback_context = Some(context);
-                                }
-                            }
-
-                            app.start_time = Instant::now();
-                            app.volume = self.player.get_volume();
-                            self.cache_play_record(t, &mut *app);
-                            app.dispatch(IoEvent::GetLyric(track_id, false));
-                            app.seek_ms.take();
-                            app.is_fetching_current_playback = false;
+                            let mut app = self.app.lock().await;
+                            self.playback_started(&mut app, track, t);
                         }
                         Err(e) => {
-                            app.handle_error(e);
+                            self.handle_error(e).await;
                         }
                     }
                 }
@@ -632,6 +693,35 @@ impl<'a> Network<'a> {
         }
     }
 
+    // 开始播放后更新播放状态，记录播放历史并加载歌词
+    fn playback_started(&mut self, app: &mut App, track: Track, t: Track) {
+        let track_id = track.id;
+        match app.current_playback_context.clone() {
+            Some(mut context) => {
+                context.is_playing = true;
+                context.item = Some(track);
+                app.current_playback_context = Some(context);
+            }
+            None => {
+                let context = CurrentlyPlaybackContext {
+                    is_playing: true,
+                    timestamp: 0,
+                    currently_playing_type: CurrentlyPlayingType::Track,
+                    repeat_state: RepeatState::Off,
+                    item: Some(track),
+                };
+                app.current_playback_context = Some(context);
+            }
+        }
+
+        app.start_time = Instant::now();
+        app.volume = self.player.get_volume();
+        self.cache_play_record(t, app);
+        app.dispatch(IoEvent::GetLyric(track_id, false));
+        app.seek_ms.take();
+        app.is_fetching_current_playback = false;
+    }
+
     fn cache_play_record(&mut self, t: Track, app: &mut App) {
         let cache_file_path = app.cache_file_path();
         let json_string = std::fs::read_to_string(&cache_file_path);
@@ -658,80 +748,64 @@ impl<'a> Network<'a> {
         }
     }
 
-    async fn load_playlist_tracks(&mut self, playlist_idk: usize) {
-        match self.cloud_music.playlist_tracks(playlist_idk).await {
-            Ok(playlist_tracks) => {
-                let mut app = self.app.lock().await;
-                app.track_table = TrackTable {
-                    tracks: playlist_tracks.tracks.clone(),
-                    selected_index: 0,
-                    context: Some(TrackTableContext::MyPlaylists),
-                };
-                app.title = String::from("歌曲列表");
-                app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
-            }
-            Err(e) => {
-                self.handle_error(e).await;
-            }
-        }
-    }
-
-    async fn load_current_user_playlists(&mut self) {
-        let result = self
-            .cloud_music
-            .current_user_playlists(self.large_search_limit, None, self.app)
-            .await;
-        match result {
-            Ok(list) => {
-                let mut app = self.app.lock().await;
-                // 我创建的歌单列表
-                let mut my_playlists = vec![];
-                // 我收藏的歌单列表
-                let mut subscribed_playlists = vec![];
-                for (i, play_list) in list.into_iter().enumerate() {
-                    if i != 0 {
-                        match play_list.subscribed {
-                            true => subscribed_playlists.push(play_list),
-                            false => my_playlists.push(play_list),
-                        }
-                    } else {
-                        app.my_like_playlist_id = play_list.id;
-                    }
+    async fn load_user(&mut self) {
+        let start = Instant::now();
+        let result = self.cloud_music.current_user().await;
+        {
+            let mut app = self.app.lock().await;
+            match result {
+                Ok(user) => {
+                    app.user = user;
                 }
-                app.playlists = Some(my_playlists);
-                app.selected_playlist_index = Some(0);
-
-                app.sub_playlists = Some(subscribed_playlists);
-                app.selected_sub_playlist_index = Some(0);
+                Err(e) => app.handle_error(e),
             }
-            Err(e) => {
-                self.handle_error(e).await;
+            if let (Some(user), Ok(manager)) = (&app.user, account_manager()) {
+                // 记住当前账号，用于切换账号
+                manager.save_current(user.user_id, &user.nickname).ok();
+            }
+            if app.user.is_none() {
+                return;
             }
+            // 获取最后播放的那条记录
+            app.read_current_play_context();
         }
+        // 喜欢的音乐、歌单列表和收藏的歌手互不依赖，并发加载，每个请求返回后立即更新界面
+        let loader = self.loader();
+        tokio::join!(
+            loader.load_like_track_id_list(),
+            loader.load_current_user_playlists(),
+            loader.load_artist_sublist()
+        );
+        log::info!("加载用户数据用时 {}ms", start.elapsed().as_millis());
     }
 
-    async fn load_user(&mut self) {
+    async fn logout(&mut self) {
+        self.cancel_tasks();
+        // 接口调用失败（如登录已过期）也需要清除本地的登录状态
+        self.cloud_music.logout().await.ok();
         let mut app = self.app.lock().await;
-        if app.user.is_none() {}
-        match self.cloud_music.current_user().await {
-            Ok(user) => {
-                app.user = user;
-            }
-            Err(e) => self.handle_error(e).await,
+        if let (Some(user), Ok(manager)) = (&app.user, account_manager()) {
+            manager.remove(user.user_id).ok();
         }
-        if app.user.is_some() {
-            // 获取最后播放的那条记录
-            app.read_current_play_context();
-            // 获取喜欢的音乐
-            app.dispatch(IoEvent::GetLikeList);
-            // 加载歌单列表
-            app.dispatch(IoEvent::GetPlaylists);
-            // 获取收藏的歌手
-            app.dispatch(IoEvent::GetArtistSubList);
+        clear_http_cache().ok();
+        app.is_logout = true;
+    }
+
+    async fn switch_account(&mut self, user_id: usize) {
+        if let Err(e) = account_manager().and_then(|manager| manager.switch(user_id)) {
+            self.handle_error(e).await;
+            return;
         }
+        self.cancel_tasks();
+        // 缓存的响应属于之前的账号
+        clear_http_cache().ok();
+        // 重新加载切换后的cookie
+        self.cloud_music = Arc::new(CloudMusic::default());
+        self.app.lock().await.clear_user_data();
+        self.load_user().await;
     }
 
-    pub async fn handle_error(&mut self, e: Error) {
+    pub async fn handle_error(&self, e: Error) {
         let mut app = self.app.lock().await;
         app.handle_error(e);
     }
@@ -772,8 +846,8 @@ pub fn panic_hook(info: &PanicInfo<'_>) {
     }
 }
 
-#[cfg(test)]
-mod tests {
+#[cfg(all(test, feature = "live-tests"))]
+mod live_tests {
     use crate::model::login::LoginForm;
     use crate::{App, IoEvent, Network, UserConfig};
     use std::sync::{mpsc, Arc};
@@ -786,9 +860,61 @@ mod tests {
         let mut network = Network::new(&app);
         network
             .login_app(LoginForm {
+                country_code: "86".to_string(),
                 phone: "xxx".to_string(),
                 password: "xxx".to_string(),
             })
             .await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::future::pending;
+
+    use tokio::sync::oneshot;
+
+    use super::{TaskKind, Tasks};
+
+    // 任务被取消时sender随之释放，receiver收到错误
+    fn pending_task() -> (tokio::task::JoinHandle<()>, oneshot::Receiver<()>) {
+        let (tx, rx) = oneshot::channel::<()>();
+        let handle = tokio::spawn(async move {
+            let _tx = tx;
+            pending::<()>().await;
+        });
+        (handle, rx)
+    }
+
+    #[tokio::test]
+    async fn test_supersede_task() {
+        let mut tasks = Tasks::default();
+        let (first, first_rx) = pending_task();
+        let (second, mut second_rx) = pending_task();
+        tasks.track_latest(TaskKind::Search, first);
+        tasks.track_latest(TaskKind::Search, second);
+        assert!(first_rx.await.is_err());
+        assert!(second_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_tasks() {
+        let mut tasks = Tasks::default();
+        let (latest, latest_rx) = pending_task();
+        let (other, other_rx) = pending_task();
+        tasks.track_latest(TaskKind::PlaylistTracks, latest);
+        tasks.track(other);
+        // 已结束的请求不再保留
+        tasks.track(tokio::spawn(async {}));
+        tokio::task::yield_now().await;
+        let (other2, other2_rx) = pending_task();
+        tasks.track(other2);
+        assert_eq!(tasks.others.len(), 2);
+
+        tasks.cancel();
+        assert!(latest_rx.await.is_err());
+        assert!(other_rx.await.is_err());
+        assert!(other2_rx.await.is_err());
+        assert!(tasks.latest.is_empty() && tasks.others.is_empty());
+    }
+}
@@ -1,20 +1,31 @@
-use std::fs::File;
+use std::fs::{self, File, OpenOptions};
 use std::io::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::Duration;
 
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use futures::channel::oneshot::Sender;
+use openssl::hash::{Hasher, MessageDigest};
 use reqwest::header::{
-    HeaderMap, ACCEPT, ACCEPT_ENCODING, CACHE_CONTROL, PRAGMA, UPGRADE_INSECURE_REQUESTS,
-    USER_AGENT,
+    HeaderMap, ACCEPT, ACCEPT_ENCODING, CACHE_CONTROL, CONTENT_RANGE, PRAGMA, RANGE,
+    UPGRADE_INSECURE_REQUESTS, USER_AGENT,
 };
-use reqwest::Method;
+use reqwest::{Method, Proxy, StatusCode};
 use tempfile::NamedTempFile;
 
+use crate::config;
+
+// 未下载完成的文件后缀
+const PART_SUFFIX: &str = "part";
+
 #[tokio::main]
-pub async fn fetch_data(url: &str, path: Option<PathBuf>, tx: Sender<String>) -> Result<(), Error> {
+pub async fn fetch_data(
+    url: &str,
+    path: Option<PathBuf>,
+    md5: Option<String>,
+    tx: Sender<String>,
+) -> Result<(), Error> {
     let mut headers = HeaderMap::new();
     headers.insert(CACHE_CONTROL, "no-cache".parse().unwrap());
     headers.insert(PRAGMA, "no-cache".parse().unwrap());
@@ -25,14 +36,22 @@ pub async fn fetch_data(url: &str, path: Option<PathBuf>, tx: Sender<String>) ->
         USER_AGENT,
         "User-Agent: Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/42.0.2311.135 Safari/537.36 Edge/13.10586".parse().unwrap(),
     );
-    let client = reqwest::Client::builder().build().expect("builder error");
-    let mut res = client
-        .request(Method::GET, url)
-        .headers(headers)
-        .send()
-        .await?;
+    let network = config::network::get();
+    if let Some(real_ip) = network.real_ip() {
+        headers.insert("X-Real-IP", real_ip.parse()?);
+    }
+    let mut client = reqwest::Client::builder().connect_timeout(network.connect_timeout());
+    if let Some(proxy) = network.audio_proxy() {
+        client = client.proxy(Proxy::all(proxy)?);
+    }
+    let client = client.build().expect("builder error");
     match path {
         None => {
+            let mut res = client
+                .request(Method::GET, url)
+                .headers(headers)
+                .send()
+                .await?;
             let mut file = NamedTempFile::new()?;
             while let Some(chunk) = res.chunk().await? {
                 Write::write_all(&mut file, &chunk[..]).unwrap();
@@ -44,13 +63,86 @@ pub async fn fetch_data(url: &str, path: Option<PathBuf>, tx: Sender<String>) ->
             send_msg(tx, file_path);
         }
         Some(path) => {
-            let file_path = path.to_string_lossy().to_string();
-            // println!("{}", file_path);
-            let mut file = File::create(file_path.as_str()).unwrap();
+            // 先写入.part文件，下载完整并校验通过后再重命名为最终文件，
+            // 避免中途退出后留下一个不完整的歌曲文件
+            let part_path = part_file_path(&path);
+            let mut downloaded = part_path.metadata().map(|m| m.len()).unwrap_or(0);
+
+            let mut res = client
+                .request(Method::GET, url)
+                .headers(range_headers(&headers, downloaded))
+                .send()
+                .await?;
+            if res.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+                // 本地的.part文件比服务端的文件还大，只能重新下载
+                downloaded = 0;
+                res = client
+                    .request(Method::GET, url)
+                    .headers(headers.clone())
+                    .send()
+                    .await?;
+            }
+            if !res.status().is_success() {
+                return Err(anyhow!("下载失败：{}", res.status()));
+            }
+
+            let content_range = res
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from);
+            let mut file = match resume_action(res.status(), content_range.as_deref(), downloaded) {
+                ResumeAction::Append => OpenOptions::new().append(true).open(&part_path)?,
+                ResumeAction::Restart => {
+                    // 服务端不支持断点续传，返回了完整的文件，从头开始下载
+                    downloaded = 0;
+                    File::create(&part_path)?
+                }
+                ResumeAction::Refetch => {
+                    // 返回的内容不是从已下载的位置开始，不带Range重新完整下载
+                    downloaded = 0;
+                    res = client
+                        .request(Method::GET, url)
+                        .headers(headers)
+                        .send()
+                        .await?;
+                    if res.status() != StatusCode::OK {
+                        return Err(anyhow!("下载失败：{}", res.status()));
+                    }
+                    File::create(&part_path)?
+                }
+            };
+            // 部分内容的响应以Content-Range中的文件总长度为准
+            let expected_len = if res.status() == StatusCode::PARTIAL_CONTENT {
+                res.headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(content_range_total)
+            } else {
+                res.content_length().map(|len| len + downloaded)
+            };
+
             while let Some(chunk) = res.chunk().await? {
-                Write::write_all(&mut file, &chunk[..]).unwrap();
+                file.write_all(&chunk[..])?;
             }
-            thread::sleep(Duration::from_secs(1));
+            file.flush()?;
+            drop(file);
+
+            let len = fs::metadata(&part_path)?.len();
+            if let Some(expected_len) = expected_len {
+                if len != expected_len {
+                    return Err(anyhow!("下载不完整：{}/{}", len, expected_len));
+                }
+            }
+            if let Some(md5) = md5 {
+                if !file_md5(&part_path)?.eq_ignore_ascii_case(&md5) {
+                    fs::remove_file(&part_path)?;
+                    return Err(anyhow!("文件校验失败"));
+                }
+            }
+            fs::rename(&part_path, &path)?;
+
+            let file_path = path.to_string_lossy().to_string();
             send_msg(tx, file_path);
         }
     }
@@ -61,3 +153,134 @@ pub async fn fetch_data(url: &str, path: Option<PathBuf>, tx: Sender<String>) ->
 fn send_msg(tx: Sender<String>, filename: String) {
     tx.send(filename).expect("send error");
 }
+
+// 歌曲文件对应的未完成文件路径，如 xxx.mp3 -> xxx.mp3.part
+fn part_file_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(PART_SUFFIX);
+    path.with_file_name(file_name)
+}
+
+fn range_headers(headers: &HeaderMap, downloaded: u64) -> HeaderMap {
+    let mut headers = headers.clone();
+    if downloaded > 0 {
+        headers.insert(RANGE, format!("bytes={}-", downloaded).parse().unwrap());
+    }
+    headers
+}
+
+// 续传请求的响应如何写入.part文件
+#[derive(Debug, PartialEq)]
+enum ResumeAction {
+    // 追加到已下载的内容之后
+    Append,
+    // 响应为完整的文件，从头写入
+    Restart,
+    // 响应只有部分内容且不是从已下载的位置开始，需重新完整下载
+    Refetch,
+}
+
+fn resume_action(status: StatusCode, content_range: Option<&str>, downloaded: u64) -> ResumeAction {
+    if status != StatusCode::PARTIAL_CONTENT {
+        return ResumeAction::Restart;
+    }
+    match content_range.and_then(content_range_start) {
+        Some(start) if start == downloaded && downloaded > 0 => ResumeAction::Append,
+        Some(0) => ResumeAction::Restart,
+        _ => ResumeAction::Refetch,
+    }
+}
+
+// 解析Content-Range的起始位置，如 "bytes 100-199/200" -> 100
+fn content_range_start(content_range: &str) -> Option<u64> {
+    content_range
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+}
+
+// 解析Content-Range中的文件总长度，如 "bytes 100-199/200" -> 200
+fn content_range_total(content_range: &str) -> Option<u64> {
+    content_range.rsplit('/').next()?.trim().parse::<u64>().ok()
+}
+
+fn file_md5(path: &Path) -> Result<String, Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hasher::new(MessageDigest::md5())?;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n])?;
+    }
+    Ok(hex::encode(hasher.finish()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use reqwest::StatusCode;
+
+    use super::{
+        content_range_start, content_range_total, file_md5, part_file_path, resume_action,
+        ResumeAction,
+    };
+
+    #[test]
+    fn test_part_file_path() {
+        let path = part_file_path(Path::new("/tmp/music/晴天-周杰伦.mp3"));
+        assert_eq!(path, Path::new("/tmp/music/晴天-周杰伦.mp3.part"));
+    }
+
+    #[test]
+    fn test_content_range_start() {
+        assert_eq!(content_range_start("bytes 100-199/200"), Some(100));
+        assert_eq!(content_range_start("bytes */200"), None);
+        assert_eq!(content_range_start("100-199/200"), None);
+    }
+
+    #[test]
+    fn test_content_range_total() {
+        assert_eq!(content_range_total("bytes 100-199/200"), Some(200));
+        assert_eq!(content_range_total("bytes 100-199/*"), None);
+    }
+
+    #[test]
+    fn test_resume_action() {
+        let partial = StatusCode::PARTIAL_CONTENT;
+        assert_eq!(
+            resume_action(partial, Some("bytes 100-199/200"), 100),
+            ResumeAction::Append
+        );
+        assert_eq!(
+            resume_action(StatusCode::OK, None, 100),
+            ResumeAction::Restart
+        );
+        assert_eq!(
+            resume_action(partial, Some("bytes 0-199/200"), 100),
+            ResumeAction::Restart
+        );
+        // 返回的范围与已下载的位置不一致时重新完整下载，不能把部分内容当作完整文件
+        assert_eq!(
+            resume_action(partial, Some("bytes 150-199/200"), 100),
+            ResumeAction::Refetch
+        );
+        assert_eq!(resume_action(partial, None, 100), ResumeAction::Refetch);
+    }
+
+    #[test]
+    fn test_file_md5() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"123456").unwrap();
+        let md5 = file_md5(file.path()).unwrap();
+        assert_eq!(md5, "e10adc3949ba59abbe56e057f20f883e");
+    }
+}
@@ -10,7 +10,7 @@ use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use futures::channel::oneshot;
-use log::debug;
+use log::{debug, error};
 use rodio::decoder::DecoderError;
 use rodio::{Decoder, Sink, Source};
 
@@ -59,10 +59,11 @@ impl Nplayer {
     pub fn play_url(
         &mut self,
         url: String,
+        md5: Option<String>,
         cache_dir: Result<PathBuf>,
         music_name_prefix: String,
     ) -> Result<()> {
-        self.player.load(url, true, cache_dir, music_name_prefix)
+        self.player.load(url, md5, true, cache_dir, music_name_prefix)
     }
 
     pub fn play_file(&mut self, file_path: String) -> Result<()> {
@@ -185,6 +186,7 @@ impl Player {
     pub fn load(
         &mut self,
         url: String,
+        md5: Option<String>,
         start_playing: bool,
         cache_dir: Result<PathBuf>,
         music_name_prefix: String,
@@ -205,7 +207,9 @@ impl Player {
         let path: Option<PathBuf> = get_music_path(Some(&url), &cache_dir, &music_name_prefix);
         let (ptx, mut prx) = oneshot::channel::<String>();
         thread::spawn(move || {
-            fetch_data(&url, path, ptx).expect("error thread task");
+            if let Err(e) = fetch_data(&url, path, md5, ptx) {
+                error!("fetch music error: {}", e);
+            }
         });
         if start_playing {
             for i in 0..3 {
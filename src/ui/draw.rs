@@ -14,15 +14,16 @@ use crate::cli::clap::BANNER;
 use crate::handlers::search::SearchResultBlock;
 use crate::model::album::AlbumUi;
 use crate::model::artist::ArtistBlock;
+use crate::model::context::TrackTableContext;
 use crate::model::enums::RepeatState;
-use crate::model::login::LoginState;
+use crate::model::login::{LoginMode, LoginState, QrLoginState};
 use crate::model::table::{ColumnId, TableHeader, TableHeaderItem, TableId, TableItem};
 use crate::ui::help::get_help_docs;
 use crate::util;
 use crate::util::{
-    create_artist_string, display_track_progress, get_artist_highlight_state, get_color,
-    get_percentage_width, get_search_results_highlight_state, get_track_progress_percentage,
-    millis_to_minutes2, BASIC_VIEW_HEIGHT, SMALL_TERMINAL_WIDTH,
+    create_artist_string, display_track_progress, format_play_count, get_artist_highlight_state,
+    get_color, get_percentage_width, get_search_results_highlight_state,
+    get_track_progress_percentage, millis_to_minutes2, BASIC_VIEW_HEIGHT, SMALL_TERMINAL_WIDTH,
 };
 
 pub fn draw_main_layout<B>(f: &mut Frame<B>, app: &App)
@@ -68,11 +69,11 @@ pub fn draw_login_page<B>(f: &mut Frame<B>, app: &App)
 where
     B: Backend,
 {
+    if app.login_info.login_mode == LoginMode::QrCode {
+        draw_qr_code_login_page(f, app);
+        return;
+    }
     let current_route = app.get_current_route();
-    let highlight_state = (
-        current_route.active_block == ActiveBlock::PhoneBlock,
-        current_route.hovered_block == ActiveBlock::PhoneBlock,
-    );
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
@@ -86,15 +87,92 @@ where
         )
         .margin(10)
         .split(f.size());
-    let text = vec![Spans::from(Span::styled(
-        "登录",
-        Style::default().add_modifier(Modifier::BOLD),
-    ))];
-    let text = Paragraph::new(text)
-        .wrap(Wrap { trim: true })
+    f.render_widget(login_title(app), chunks[0]);
+
+    match app.login_info.login_mode {
+        LoginMode::Email => draw_email_input(f, app, chunks[1]),
+        LoginMode::Cookie => draw_cookie_input(f, app, chunks[1]),
+        _ => draw_phone_input(f, app, chunks[1]),
+    }
+
+    if app.login_info.login_mode == LoginMode::Sms {
+        draw_captcha_input(f, app, chunks[2]);
+    } else if app.login_info.login_mode == LoginMode::Cookie {
+        let tips = Paragraph::new(vec![
+            Spans::from("支持以下任意一种："),
+            Spans::from("1. 从浏览器复制的Cookie，如 MUSIC_U=xxx; __csrf=xxx"),
+            Spans::from("2. MUSIC_U的值"),
+            Spans::from("3. Netscape格式的cookies.txt文件路径"),
+        ])
+        .style(Style::default().fg(app.user_config.theme.inactive))
+        .wrap(Wrap { trim: true });
+        f.render_widget(tips, chunks[2]);
+    } else {
+        let password_input_string: String = app.login_info.password.clone();
+        let password_line = Text::from((&password_input_string).as_str());
+        let current_route = app.get_current_route();
+        let highlight_state = (
+            current_route.active_block == ActiveBlock::PasswordBlock,
+            current_route.hovered_block == ActiveBlock::PasswordBlock,
+        );
+        let password_input = Paragraph::new(password_line).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(
+                    "密码",
+                    get_color(highlight_state, app.user_config.theme),
+                ))
+                .border_style(get_color(highlight_state, app.user_config.theme)),
+        );
+        f.render_widget(password_input, chunks[2]);
+    }
+
+    let hchunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(3)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+        .split(chunks[3]);
+
+    let ok_text = Span::raw("确认");
+
+    let ok = Paragraph::new(ok_text)
+        .style(match &app.login_info.login_state {
+            LoginState::NoActive | LoginState::Cancel => {
+                Style::default().fg(app.user_config.theme.inactive)
+            }
+            LoginState::Confirm => Style::default().fg(app.user_config.theme.active),
+        })
+        .alignment(Alignment::Center);
+
+    f.render_widget(ok, hchunks[0]);
+
+    let cancel_text = Span::raw("取消");
+    let cancel = Paragraph::new(cancel_text)
+        .style(match &app.login_info.login_state {
+            LoginState::NoActive => Style::default().fg(app.user_config.theme.inactive),
+            LoginState::Confirm => Style::default().fg(app.user_config.theme.inactive),
+            LoginState::Cancel => Style::default().fg(app.user_config.theme.active),
+        })
         .alignment(Alignment::Center);
 
-    f.render_widget(text, chunks[0]);
+    f.render_widget(cancel, hchunks[1]);
+
+    // 弹窗最后绘制，覆盖在其他控件之上
+    if current_route.active_block == ActiveBlock::CountryBlock {
+        draw_country_picker(f, app);
+    }
+}
+
+// 区号及手机号输入框
+fn draw_phone_input<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::PhoneBlock,
+        current_route.hovered_block == ActiveBlock::PhoneBlock,
+    );
 
     let input_string: String = app.login_info.phone.clone();
 
@@ -108,55 +186,253 @@ where
             ))
             .border_style(get_color(highlight_state, app.user_config.theme)),
     );
-    f.render_widget(phone_input, chunks[1]);
+    let phone_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(10), Constraint::Min(1)].as_ref())
+        .split(layout_chunk);
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::CountryBlock,
+        current_route.hovered_block == ActiveBlock::CountryBlock,
+    );
+    let country_code = Paragraph::new(format!("+{}", app.login_info.country_code)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(
+                "区号",
+                get_color(highlight_state, app.user_config.theme),
+            ))
+            .border_style(get_color(highlight_state, app.user_config.theme)),
+    );
+    f.render_widget(country_code, phone_chunks[0]);
+    f.render_widget(phone_input, phone_chunks[1]);
+}
 
-    let password_input_string: String = app.login_info.password.clone();
-    let password_line = Text::from((&password_input_string).as_str());
+fn draw_email_input<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
     let current_route = app.get_current_route();
     let highlight_state = (
-        current_route.active_block == ActiveBlock::PasswordBlock,
-        current_route.hovered_block == ActiveBlock::PasswordBlock,
+        current_route.active_block == ActiveBlock::EmailBlock,
+        current_route.hovered_block == ActiveBlock::EmailBlock,
     );
-    let password_input = Paragraph::new(password_line).block(
+    let email_input = Paragraph::new(Text::from(app.login_info.email.as_str())).block(
         Block::default()
             .borders(Borders::ALL)
             .title(Span::styled(
-                "密码",
+                "网易邮箱",
                 get_color(highlight_state, app.user_config.theme),
             ))
             .border_style(get_color(highlight_state, app.user_config.theme)),
     );
-    f.render_widget(password_input, chunks[2]);
+    f.render_widget(email_input, layout_chunk);
+}
 
-    let hchunks = Layout::default()
+fn draw_cookie_input<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::CookieBlock,
+        current_route.hovered_block == ActiveBlock::CookieBlock,
+    );
+    let cookie_input = Paragraph::new(Text::from(app.login_info.cookie.as_str()))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(
+                    "Cookie",
+                    get_color(highlight_state, app.user_config.theme),
+                ))
+                .border_style(get_color(highlight_state, app.user_config.theme)),
+        );
+    f.render_widget(cookie_input, layout_chunk);
+}
+
+// 国家/地区选择框
+fn draw_country_picker<B>(f: &mut Frame<B>, app: &App)
+where
+    B: Backend,
+{
+    let bounds = f.size();
+    let width = std::cmp::min(bounds.width.saturating_sub(2), 40);
+    let height = std::cmp::min(bounds.height.saturating_sub(2), 20);
+    let rect = Rect::new(
+        (bounds.width - width) / 2,
+        (bounds.height - height) / 2,
+        width,
+        height,
+    );
+    f.render_widget(Clear, rect);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(rect);
+
+    let search = Paragraph::new(app.login_info.country_search.as_str()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(
+                "搜索国家/地区",
+                Style::default().fg(app.user_config.theme.active),
+            ))
+            .border_style(Style::default().fg(app.user_config.theme.active)),
+    );
+    f.render_widget(search, chunks[0]);
+
+    let countries = app.login_info.filtered_countries();
+    let items = countries
+        .iter()
+        .map(|c| ListItem::new(format!("{} {} +{}", c.zh, c.en, c.code)))
+        .collect::<Vec<_>>();
+    let title = if app.login_info.countries.is_empty() {
+        "加载中……"
+    } else {
+        "↑/↓ 选择，Enter 确认"
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(
+                    title,
+                    Style::default().fg(app.user_config.theme.inactive),
+                ))
+                .border_style(Style::default().fg(app.user_config.theme.active)),
+        )
+        .style(Style::default().fg(app.user_config.theme.text))
+        .highlight_style(
+            Style::default()
+                .fg(app.user_config.theme.active)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    if !countries.is_empty() {
+        state.select(Some(app.login_info.country_selected_index));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+// 验证码输入框及发送按钮
+fn draw_captcha_input<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .horizontal_margin(3)
-        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
-        .split(chunks[3]);
+        .constraints([Constraint::Min(1), Constraint::Length(18)].as_ref())
+        .split(layout_chunk);
 
-    let ok_text = Span::raw("确认");
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::CaptchaBlock,
+        current_route.hovered_block == ActiveBlock::CaptchaBlock,
+    );
+    let captcha_input = Paragraph::new(Text::from(app.login_info.captcha.as_str())).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(
+                "验证码",
+                get_color(highlight_state, app.user_config.theme),
+            ))
+            .border_style(get_color(highlight_state, app.user_config.theme)),
+    );
+    f.render_widget(captcha_input, chunks[0]);
 
-    let ok = Paragraph::new(ok_text)
-        .style(match &app.login_info.login_state {
-            LoginState::NoActive | LoginState::Cancel => {
-                Style::default().fg(app.user_config.theme.inactive)
-            }
-            LoginState::Confirm => Style::default().fg(app.user_config.theme.active),
-        })
-        .alignment(Alignment::Center);
+    let highlight_state = (
+        false,
+        current_route.hovered_block == ActiveBlock::SendCaptchaButton,
+    );
+    let remaining = app.login_info.captcha_cooldown_remaining();
+    let send_text = if remaining > 0 {
+        format!("{}秒后重发", remaining)
+    } else {
+        "发送验证码".to_string()
+    };
+    let send_button = Paragraph::new(send_text)
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(get_color(highlight_state, app.user_config.theme)),
+        );
+    f.render_widget(send_button, chunks[1]);
+}
 
-    f.render_widget(ok, hchunks[0]);
+// 登录标题及可切换的登录方式
+fn login_title(app: &App) -> Paragraph<'_> {
+    let mut modes = vec![];
+    for (i, mode) in LoginMode::ALL.iter().enumerate() {
+        if i > 0 {
+            modes.push(Span::raw(" | "));
+        }
+        let style = if *mode == app.login_info.login_mode {
+            Style::default().fg(app.user_config.theme.active)
+        } else {
+            Style::default().fg(app.user_config.theme.inactive)
+        };
+        modes.push(Span::styled(mode.title(), style));
+    }
+    modes.push(Span::raw("  (Ctrl-t 切换)"));
+    let text = vec![
+        Spans::from(Span::styled(
+            "登录",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(modes),
+    ];
+    Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center)
+}
 
-    let cancel_text = Span::raw("取消");
-    let cancel = Paragraph::new(cancel_text)
-        .style(match &app.login_info.login_state {
-            LoginState::NoActive => Style::default().fg(app.user_config.theme.inactive),
-            LoginState::Confirm => Style::default().fg(app.user_config.theme.inactive),
-            LoginState::Cancel => Style::default().fg(app.user_config.theme.active),
-        })
-        .alignment(Alignment::Center);
+fn draw_qr_code_login_page<B>(f: &mut Frame<B>, app: &App)
+where
+    B: Backend,
+{
+    let qr_login = app.login_info.qr_login.as_ref();
+    let image_height = qr_login.map_or(1, |qr| qr.image.lines().count() as u16);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(image_height),
+                Constraint::Length(2),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .margin(2)
+        .split(f.size());
 
-    f.render_widget(cancel, hchunks[1]);
+    f.render_widget(login_title(app), chunks[0]);
+
+    let (image, tip) = match qr_login {
+        Some(qr) => {
+            let tip = match qr.state {
+                QrLoginState::Waiting => "请使用网易云音乐APP扫码登录",
+                QrLoginState::Scanned => "已扫码，请在手机上确认登录",
+                QrLoginState::Confirmed => "登录成功",
+                QrLoginState::Expired => "二维码已过期，按 Enter 刷新",
+                QrLoginState::Failed => "查询扫码状态失败，按 Enter 刷新",
+            };
+            (qr.image.as_str(), tip)
+        }
+        None => ("", "正在获取二维码……"),
+    };
+    let image = Paragraph::new(Text::from(image)).alignment(Alignment::Center);
+    f.render_widget(image, chunks[1]);
+
+    let tip = Paragraph::new(Span::styled(
+        tip,
+        Style::default().fg(app.user_config.theme.active),
+    ))
+    .alignment(Alignment::Center);
+    f.render_widget(tip, chunks[2]);
 }
 
 pub fn draw_dialog<B>(f: &mut Frame<B>, app: &App)
@@ -332,6 +608,7 @@ where
                 RepeatState::Track => "O1",
                 RepeatState::Context => "O",
                 RepeatState::Shuffle => "X",
+                RepeatState::Intelligence => "♥",
             };
             let title = format!(
                 "{:-7} {:-1}% {:-1} ",
@@ -472,6 +749,18 @@ where
         RouteId::AlbumTracks => {
             draw_album_detail_table(f, app, chunks[1]);
         }
+        RouteId::Accounts => {
+            draw_accounts_table(f, app, chunks[1]);
+        }
+        RouteId::PersonalFm => {
+            draw_personal_fm(f, app, chunks[1]);
+        }
+        RouteId::Toplists => {
+            draw_toplist_table(f, app, chunks[1]);
+        }
+        RouteId::Discover => {
+            draw_discover(f, app, chunks[1]);
+        }
         RouteId::PhoneBlock => {}
         RouteId::PasswordBlock => {}
         RouteId::LoginButton => {}
@@ -693,6 +982,329 @@ where
     );
 }
 
+// 排行榜列表，显示更新频率和前三名
+pub fn draw_toplist_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let header = TableHeader {
+        id: TableId::Toplist,
+        items: vec![
+            TableHeaderItem {
+                text: "榜单",
+                width: get_percentage_width(layout_chunk.width, 0.2),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "更新频率",
+                width: get_percentage_width(layout_chunk.width, 0.15),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "前三名",
+                width: get_percentage_width(layout_chunk.width, 0.65),
+                ..Default::default()
+            },
+        ],
+    };
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Toplists,
+        current_route.hovered_block == ActiveBlock::Toplists,
+    );
+
+    let items: Vec<TableItem> = app
+        .toplists
+        .iter()
+        .map(|item| TableItem {
+            id: item.id,
+            fee: 0,
+            format: vec![
+                item.name.to_owned(),
+                item.update_frequency.to_owned(),
+                item.preview(),
+            ],
+        })
+        .collect();
+
+    draw_table(
+        f,
+        app,
+        layout_chunk,
+        ("排行榜", &header),
+        &items,
+        app.toplists_selected_index,
+        highlight_state,
+    );
+}
+
+// 发现歌单，左侧为分类，右侧为该分类的歌单
+pub fn draw_discover<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
+        .split(layout_chunk);
+
+    let current_route = app.get_current_route();
+    let categories_highlight_state = (
+        current_route.active_block == ActiveBlock::DiscoverCategories,
+        current_route.hovered_block == ActiveBlock::DiscoverCategories,
+    );
+    let discover = &app.discover;
+    draw_selectable_list(
+        f,
+        app,
+        chunks[0],
+        &format!(
+            "分类 ({} {})",
+            discover.order.label(),
+            app.user_config.keys.toggle_playlist_order
+        ),
+        discover.current_categories(),
+        categories_highlight_state,
+        Some(discover.category_index),
+    );
+
+    let header = TableHeader {
+        id: TableId::Playlist,
+        items: vec![
+            TableHeaderItem {
+                id: ColumnId::Liked,
+                text: "",
+                width: 2,
+            },
+            TableHeaderItem {
+                id: ColumnId::Title,
+                text: "歌单",
+                width: get_percentage_width(chunks[1].width, 0.45),
+            },
+            TableHeaderItem {
+                text: "创建者",
+                width: get_percentage_width(chunks[1].width, 0.25),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "播放数",
+                width: get_percentage_width(chunks[1].width, 0.15),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "歌曲数",
+                width: get_percentage_width(chunks[1].width, 0.15),
+                ..Default::default()
+            },
+        ],
+    };
+    let playlists_highlight_state = (
+        current_route.active_block == ActiveBlock::DiscoverPlaylists,
+        current_route.hovered_block == ActiveBlock::DiscoverPlaylists,
+    );
+    // 已收藏的歌单显示喜欢图标
+    let subscribed = |id: usize| {
+        app.sub_playlists
+            .as_ref()
+            .is_some_and(|playlists| playlists.iter().any(|p| p.id == id))
+    };
+    let items = discover
+        .playlists
+        .iter()
+        .map(|item| TableItem {
+            id: item.id,
+            fee: 0,
+            format: vec![
+                if subscribed(item.id) {
+                    app.user_config.padded_liked_icon()
+                } else {
+                    "".to_string()
+                },
+                item.name.to_owned(),
+                item.creator
+                    .as_ref()
+                    .map(|creator| creator.nickname.to_owned())
+                    .unwrap_or_default(),
+                format_play_count(item.play_count),
+                format!("{}首", item.track_count),
+            ],
+        })
+        .collect::<Vec<TableItem>>();
+    let title = if discover.is_fetching && discover.playlists.is_empty() {
+        format!(
+            "{} · {} (加载中)",
+            discover.current_category(),
+            discover.order.label()
+        )
+    } else {
+        format!(
+            "{} · {} (已加载{}/共{}个)",
+            discover.current_category(),
+            discover.order.label(),
+            discover.playlists.len(),
+            discover.total
+        )
+    };
+
+    draw_table(
+        f,
+        app,
+        chunks[1],
+        (&title, &header),
+        &items,
+        discover.selected_index,
+        playlists_highlight_state,
+    );
+}
+
+// 私人FM的当前歌曲和待播放歌曲
+pub fn draw_personal_fm<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let header = TableHeader {
+        id: TableId::Song,
+        items: vec![
+            TableHeaderItem {
+                id: ColumnId::Liked,
+                text: "",
+                width: 2,
+            },
+            TableHeaderItem {
+                id: ColumnId::Title,
+                text: "歌曲",
+                width: get_percentage_width(layout_chunk.width, 0.3) - 5,
+            },
+            TableHeaderItem {
+                text: "歌手",
+                width: get_percentage_width(layout_chunk.width, 0.3),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "专辑",
+                width: get_percentage_width(layout_chunk.width, 0.3),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "时长",
+                width: get_percentage_width(layout_chunk.width, 0.1),
+                ..Default::default()
+            },
+        ],
+    };
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::PersonalFm,
+        current_route.hovered_block == ActiveBlock::PersonalFm,
+    );
+    let fm = &app.personal_fm;
+    let items = fm
+        .current()
+        .into_iter()
+        .chain(fm.upcoming())
+        .map(|item| TableItem {
+            id: item.id,
+            fee: item.fee,
+            format: vec![
+                "".to_string(),
+                item.name.to_owned(),
+                create_artist_string(&item.artists),
+                item.album.name.to_owned().unwrap_or_default(),
+                millis_to_minutes2(item.duration),
+            ],
+        })
+        .collect::<Vec<TableItem>>();
+    let title = if items.is_empty() && fm.is_fetching {
+        "私人FM（加载中…）".to_string()
+    } else if app.is_playing_fm() {
+        format!("私人FM（{} 不喜欢）", app.user_config.keys.fm_trash)
+    } else {
+        format!(
+            "私人FM（Enter 继续播放 {} 不喜欢）",
+            app.user_config.keys.fm_trash
+        )
+    };
+
+    draw_table(
+        f,
+        app,
+        layout_chunk,
+        (&title, &header),
+        &items,
+        0,
+        highlight_state,
+    );
+}
+
+pub fn draw_accounts_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+    B: Backend,
+{
+    let header = TableHeader {
+        id: TableId::Account,
+        items: vec![
+            TableHeaderItem {
+                text: "昵称",
+                width: get_percentage_width(layout_chunk.width, 0.5),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "用户ID",
+                width: get_percentage_width(layout_chunk.width, 0.3),
+                ..Default::default()
+            },
+            TableHeaderItem {
+                text: "",
+                width: get_percentage_width(layout_chunk.width, 0.2),
+                ..Default::default()
+            },
+        ],
+    };
+
+    let current_route = app.get_current_route();
+    let highlight_state = (
+        current_route.active_block == ActiveBlock::Accounts,
+        current_route.hovered_block == ActiveBlock::Accounts,
+    );
+
+    let current_user_id = app.user.as_ref().map(|u| u.user_id);
+    let items: Vec<TableItem> = app
+        .accounts
+        .iter()
+        .map(|item| TableItem {
+            id: item.user_id,
+            fee: 0,
+            format: vec![
+                item.nickname.clone(),
+                item.user_id.to_string(),
+                if current_user_id == Some(item.user_id) {
+                    "当前账号".to_string()
+                } else {
+                    "".to_string()
+                },
+            ],
+        })
+        .collect();
+    let title = format!(
+        "账号（Enter 切换 {} 添加 {} 删除 {} 退出登录）",
+        app.user_config.keys.add_account,
+        app.user_config.keys.remove_account,
+        app.user_config.keys.logout
+    );
+
+    draw_table(
+        f,
+        app,
+        layout_chunk,
+        (&title, &header),
+        &items,
+        app.accounts_selected_index,
+        highlight_state,
+    );
+}
+
 pub fn draw_search_results<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
     B: Backend,
@@ -1007,6 +1619,8 @@ where
         current_route.active_block == ActiveBlock::TrackTable,
         current_route.hovered_block == ActiveBlock::TrackTable,
     );
+    // 心动模式中推荐的相似歌曲单独标出
+    let is_intelligence = app.track_table.context == Some(TrackTableContext::Intelligence);
     let items = app
         .track_table
         .tracks
@@ -1016,19 +1630,34 @@ where
             fee: item.fee,
             format: vec![
                 "".to_string(),
-                item.name.to_owned(),
+                if is_intelligence && app.intelligence.recommended_ids.contains(&item.id) {
+                    format!("{}（推荐）", item.name)
+                } else {
+                    item.name.to_owned()
+                },
                 create_artist_string(&item.artists),
                 item.album.name.to_owned().unwrap(),
                 millis_to_minutes2(item.duration),
             ],
         })
         .collect::<Vec<TableItem>>();
+    // 分页加载的歌单显示歌曲总数
+    let title = match &app.track_table.pagination {
+        Some(pagination) if pagination.loaded < pagination.track_ids.len() => format!(
+            "{} (已加载{}/共{}首)",
+            app.title,
+            app.track_table.tracks.len(),
+            app.track_table.total()
+        ),
+        Some(_) => format!("{} (共{}首)", app.title, app.track_table.total()),
+        None => app.title.clone(),
+    };
     // let items = vec![];
     draw_table(
         f,
         app,
         layout_chunk,
-        (&app.title, &header),
+        (&title, &header),
         &items,
         app.track_table.selected_index,
         highlight_state,
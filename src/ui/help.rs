@@ -67,6 +67,11 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             key_bindings.basic_view.to_string(),
             String::from("全局"),
         ],
+        vec![
+            String::from("账号管理"),
+            key_bindings.manage_accounts.to_string(),
+            String::from("全局"),
+        ],
         vec![
             String::from("使用一个区块变为悬浮状态"),
             String::from("<Esc>"),
@@ -112,11 +117,41 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
             key_bindings.add_item_to_queue.to_string(),
             String::from("列表操作"),
         ],
+        vec![
+            String::from("以选中歌曲开始相似歌曲电台"),
+            key_bindings.start_radio.to_string(),
+            String::from("列表操作"),
+        ],
         vec![
             String::from("取消收藏歌单"),
             String::from("D"),
             String::from("列表操作"),
         ],
+        vec![
+            String::from("不喜欢当前歌曲并跳过"),
+            key_bindings.fm_trash.to_string(),
+            String::from("私人FM"),
+        ],
+        vec![
+            String::from("切换歌单排序(最热/最新/精品)"),
+            key_bindings.toggle_playlist_order.to_string(),
+            String::from("发现歌单"),
+        ],
+        vec![
+            String::from("添加账号"),
+            key_bindings.add_account.to_string(),
+            String::from("账号管理"),
+        ],
+        vec![
+            String::from("删除账号"),
+            key_bindings.remove_account.to_string(),
+            String::from("账号管理"),
+        ],
+        vec![
+            String::from("退出登录"),
+            key_bindings.logout.to_string(),
+            String::from("账号管理"),
+        ],
         vec![
             String::from("向下滚动20行"),
             key_bindings.next_page.to_string(),
@@ -1,6 +1,7 @@
 use std::cmp::{max, min};
 use std::io;
 use std::io::{stdout, Stdout};
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -23,6 +24,7 @@ use crate::event;
 use crate::event::{Event, IoEvent};
 use crate::event::{Events, Key};
 use crate::handlers;
+use crate::http::error::ApiError;
 use crate::http::get_or_build_cookie_paths;
 use crate::util::SMALL_TERMINAL_HEIGHT;
 
@@ -47,11 +49,38 @@ pub async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<
     let events = event::Events::new(user_config.behavior.tick_rate_milliseconds);
     let cookie_path = get_or_build_cookie_paths()?;
 
-    let mut is_close = false;
     if !cookie_path.exists() {
         let mut app = app.lock().await;
         app.set_current_route_state(Some(ActiveBlock::PhoneBlock), Some(ActiveBlock::PhoneBlock));
     }
+    loop {
+        if !login(app, &mut terminal, &events, &cookie_path).await? {
+            break;
+        }
+        if !render_app_layout(app, &mut terminal, &events).await? {
+            break;
+        }
+        // 登录已过期或退出登录，删除当前cookie并回到登录页
+        std::fs::remove_file(&cookie_path).ok();
+        let mut app = app.lock().await;
+        let is_session_expired = app.is_session_expired;
+        app.reset_login();
+        if is_session_expired {
+            app.push_navigation_stack(RouteId::Error, ActiveBlock::Error);
+            app.api_error = ApiError::Auth.to_string();
+        }
+    }
+    close_application(terminal)?;
+    Ok(())
+}
+
+// 登录页面，返回false表示用户取消登录
+async fn login(
+    app: &Arc<Mutex<App>>,
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    events: &Events,
+    cookie_path: &Path,
+) -> Result<bool> {
     loop {
         let mut app = app.lock().await;
         if !cookie_path.exists() && !app.login_info.is_login_success {
@@ -63,49 +92,63 @@ pub async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<
                     draw::draw_login_page(f, &app);
                 }
             })?;
-            if let Event::Input(key) = events.next()? {
-                if key == Key::Ctrl('c') {
-                    is_close = true;
-                    break;
+            match events.next()? {
+                Event::Input(key) => {
+                    if key == Key::Ctrl('c') {
+                        return Ok(false);
+                    }
+                    let current_active_block = app.get_current_route().active_block;
+                    if key == Key::Ctrl('t') && current_active_block != ActiveBlock::Error {
+                        handlers::switch_login_mode(&mut app);
+                    } else if current_active_block == ActiveBlock::PhoneBlock {
+                        handlers::phone_input_handler(key, &mut app);
+                    } else if current_active_block == ActiveBlock::PasswordBlock {
+                        handlers::password_input_handler(key, &mut app);
+                    } else if current_active_block == ActiveBlock::CookieBlock {
+                        handlers::cookie_input_handler(key, &mut app);
+                    } else if current_active_block == ActiveBlock::EmailBlock {
+                        handlers::email_input_handler(key, &mut app);
+                    } else if current_active_block == ActiveBlock::CountryBlock {
+                        handlers::country_handler(key, &mut app);
+                    } else if current_active_block == ActiveBlock::CaptchaBlock {
+                        handlers::captcha_input_handler(key, &mut app);
+                    } else if current_active_block == ActiveBlock::LoginButton {
+                        handlers::login_button_handler(key, &mut app);
+                    } else if current_active_block == ActiveBlock::QrCodeBlock {
+                        handlers::qr_code_handler(key, &mut app);
+                    } else {
+                        handlers::handle_app_login(key, &mut app);
+                    }
                 }
-                let current_active_block = app.get_current_route().active_block;
-                if current_active_block == ActiveBlock::PhoneBlock {
-                    handlers::phone_input_handler(key, &mut app);
-                } else if current_active_block == ActiveBlock::PasswordBlock {
-                    handlers::password_input_handler(key, &mut app);
-                } else if current_active_block == ActiveBlock::LoginButton {
-                    handlers::login_button_handler(key, &mut app);
-                } else {
-                    handlers::handle_app_login(key, &mut app);
+                Event::Tick => {
+                    app.update_login_on_tick();
                 }
             }
             if app.login_info.cancel_login {
-                is_close = true;
-                break;
+                return Ok(false);
             }
         } else {
-            let cookie = std::fs::read_to_string(&cookie_path).unwrap();
+            let cookie = std::fs::read_to_string(cookie_path).unwrap();
             if cookie.contains("MUSIC_U") {
                 app.navigation_stack = vec![DEFAULT_ROUTE];
+                // 忽略登录前仍在进行的请求产生的登录过期错误
+                app.is_session_expired = false;
+                app.is_logout = false;
                 break;
-            } else if std::fs::remove_file(&cookie_path).is_ok() {
+            } else if std::fs::remove_file(cookie_path).is_ok() {
             }
         }
     }
 
-    if !is_close {
-        render_app_layout(app, terminal, events).await?;
-    } else {
-        close_application(terminal)?;
-    }
-    Ok(())
+    Ok(true)
 }
 
+// 主界面，返回true表示需要回到登录页重新登录
 async fn render_app_layout(
     app: &Arc<Mutex<App>>,
-    mut terminal: Terminal<CrosstermBackend<Stdout>>,
-    events: Events,
-) -> Result<()> {
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    events: &Events,
+) -> Result<bool> {
     let mut is_first_render = true;
     loop {
         let mut app = app.lock().await;
@@ -204,10 +247,13 @@ async fn render_app_layout(
             app.help_docs_size = help::get_help_docs(&app.user_config.keys).len() as u32;
             is_first_render = false;
         }
+
+        if app.is_session_expired || app.is_logout {
+            return Ok(true);
+        }
     }
-    close_application(terminal)?;
 
-    Ok(())
+    Ok(false)
 }
 fn close_application(mut terminal: Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
     disable_raw_mode()?;
@@ -2,7 +2,10 @@ use crate::app::{ActiveBlock, App};
 use crate::config::theme::Theme;
 use crate::handlers::search::SearchResultBlock;
 use crate::model::artist::{Artist, ArtistBlock};
+use anyhow::Result;
 use openssl::hash::{hash, MessageDigest};
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
 use std::ffi::OsStr;
 use std::ops::Add;
 use std::path::{Path, PathBuf};
@@ -142,6 +145,16 @@ pub fn millis_to_minutes2(millis: usize) -> String {
     }
 }
 
+/// 播放数，超过一万时以万、亿为单位，如"12.3万"
+pub fn format_play_count(count: u64) -> String {
+    match count {
+        0..=9_999 => count.to_string(),
+        // 保留一位小数后达到一万万的按亿显示
+        10_000..=99_994_999 => format!("{:.1}万", count as f64 / 10_000.0),
+        _ => format!("{:.1}亿", count as f64 / 100_000_000.0),
+    }
+}
+
 pub fn millis_to_minutes(millis: u128) -> String {
     let minutes = millis / 60000;
     let seconds = (millis % 60000) / 1000;
@@ -161,3 +174,31 @@ pub fn millis_to_minutes(millis: u128) -> String {
 pub fn md5_encode(data: &[u8]) -> String {
     hex::encode(hash(MessageDigest::md5(), data).unwrap())
 }
+
+// 用Unicode半角方块渲染二维码，终端一般为深色背景，所以颜色取反
+pub fn render_qr_code(data: &str) -> Result<String> {
+    let code = QrCode::new(data.as_bytes())?;
+    let image = code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build();
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_play_count;
+
+    #[test]
+    fn test_format_play_count() {
+        assert_eq!(format_play_count(9_999), "9999");
+        assert_eq!(format_play_count(10_000), "1.0万");
+        assert_eq!(format_play_count(123_456), "12.3万");
+        assert_eq!(format_play_count(99_994_999), "9999.5万");
+        assert_eq!(format_play_count(99_995_000), "1.0亿");
+        assert_eq!(format_play_count(99_999_999), "1.0亿");
+        assert_eq!(format_play_count(1_234_567_890), "12.3亿");
+    }
+}